@@ -1,10 +1,17 @@
 use crate::config::{BarConfig, BarPosition, MarginConfig, ModuleConfig};
-use crate::modules::{BarModuleFactory, ModuleInfo, ModuleLocation, ModuleRef};
+#[cfg(feature = "ipc")]
+use crate::ipc::{Event, events};
+use crate::modules::{
+    AnyModuleFactory, BarModuleFactory, ModuleInfo, ModuleLocation, ModuleRef, ModuleTasks,
+};
 use crate::popup::Popup;
 use crate::{Ironbar, rc_mut};
+use color_eyre::Result;
 use gtk::gdk::Monitor;
 use gtk::prelude::*;
-use gtk::{Application, ApplicationWindow, CenterBox, EventControllerMotion, Orientation, Window};
+use gtk::{
+    Application, ApplicationWindow, CenterBox, EventControllerMotion, Orientation, Widget, Window,
+};
 use gtk_layer_shell::LayerShell;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -17,11 +24,52 @@ enum Inner {
         config: Option<BarConfig>,
     },
     Loaded {
-        module_refs: Vec<ModuleRef>,
+        /// The bar-level config, without any modules.
+        config: BarConfig,
+        monitor: Monitor,
+        modules: Rc<RefCell<Vec<BarModule>>>,
         popup: Rc<Popup>,
     },
 }
 
+/// A module loaded onto the bar,
+/// alongside the config it was created from
+/// and the tasks spawned for it.
+#[derive(Debug, Clone)]
+struct BarModule {
+    config: ModuleConfig,
+    module: ModuleRef,
+    tasks: Rc<ModuleTasks>,
+}
+
+impl BarModule {
+    /// Gets the revealer wrapping the module widget,
+    /// which is the direct child of the container.
+    fn revealer(&self) -> Widget {
+        self.module
+            .root_widget
+            .parent()
+            .expect("module widget to be inside revealer")
+    }
+
+    /// Creates a module from its config.
+    fn create(
+        config: ModuleConfig,
+        module_factory: &AnyModuleFactory,
+        container: &gtk::Box,
+        info: &ModuleInfo,
+    ) -> Result<Self> {
+        let (module, tasks) =
+            ModuleTasks::collect(|| config.clone().create(module_factory, container, info));
+
+        module.map(|module| Self {
+            config,
+            module,
+            tasks: Rc::new(tasks),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 struct AutohideState {
     hotspot_window: Window,
@@ -96,6 +144,17 @@ impl Bar {
 
         window.set_child(Some(&content));
 
+        #[cfg(feature = "ipc")]
+        {
+            let name = name.clone();
+            window.connect_visible_notify(move |window| {
+                events::emit(Event::BarVisibility {
+                    name: name.clone(),
+                    visible: window.is_visible(),
+                });
+            });
+        }
+
         Self {
             name,
             monitor_name,
@@ -118,7 +177,7 @@ impl Bar {
             return self;
         };
 
-        let Some(config) = config.take() else {
+        let Some(mut config) = config.take() else {
             return self;
         };
 
@@ -144,12 +203,21 @@ impl Bar {
         let anchor_to_edges = config.anchor_to_edges;
         let margin = config.margin;
 
-        let instance = Rc::new(self.clone());
-        let load_result = self.load_modules(instance, config, monitor);
+        let module_configs = take_modules(&mut config);
+
+        let popup = Rc::new(Popup::new(
+            &self.name,
+            // popup ignores module location so can bodge this for now
+            &self.module_info(&self.app(), monitor, ModuleLocation::Left),
+            config.popup_gap,
+            config.popup_autohide,
+        ));
+
+        let modules = self.load_modules(vec![], module_configs, &popup, monitor);
 
         let autohide_state = if let Some(autohide) = autohide {
             let hotspot_window = Window::new();
-            self.setup_autohide(&hotspot_window, load_result.popup.clone(), autohide);
+            self.setup_autohide(&hotspot_window, popup.clone(), autohide);
             self.setup_layer_shell(
                 &hotspot_window,
                 false,
@@ -175,15 +243,165 @@ impl Bar {
 
         *self.autohide_state.borrow_mut() = autohide_state;
         self.inner = Inner::Loaded {
-            popup: load_result.popup,
-            module_refs: load_result.module_refs,
+            config,
+            monitor: monitor.clone(),
+            modules: rc_mut!(modules),
+            popup,
         };
 
         self
     }
 
+    /// Applies a new config to an initialized bar,
+    /// recreating only those modules whose config has changed.
+    /// Unchanged modules keep their widgets and state.
+    ///
+    /// Returns `false` without making any changes if a bar-level option has changed,
+    /// in which case the bar must be closed and recreated instead.
+    pub fn update(&self, config: &BarConfig) -> bool {
+        let Inner::Loaded {
+            config: current,
+            monitor,
+            modules,
+            popup,
+        } = &self.inner
+        else {
+            return false;
+        };
+
+        let mut config = config.clone();
+        let module_configs = take_modules(&mut config);
+
+        if *current != config {
+            return false;
+        }
+
+        debug!("Updating modules on bar '{}'", self.name);
+
+        // take the modules out while loading,
+        // as new modules may access the bar while being created.
+        let existing = modules.take();
+        let loaded = self.load_modules(existing, module_configs, popup, monitor);
+        *modules.borrow_mut() = loaded;
+
+        true
+    }
+
+    /// Adds a module to the bar.
+    ///
+    /// The module is inserted at `index` within the `location` container,
+    /// or appended to it if not set.
+    pub fn add_module(
+        &self,
+        config: ModuleConfig,
+        location: ModuleLocation,
+        index: Option<usize>,
+    ) -> Result<()> {
+        let (monitor, modules, popup) = self.loaded();
+
+        let app = self.app();
+        let module_factory = self.module_factory(popup);
+        let info = self.module_info(&app, monitor, location.clone());
+
+        let container = self.container(&location);
+        self.attach_container(&location, true);
+
+        let module = BarModule::create(config, &module_factory, container, &info)?;
+
+        let mut modules = modules.borrow_mut();
+        let position = insert_position(&modules, &location, index);
+        modules.insert(position, module);
+
+        self.reorder(&modules, &location);
+
+        Ok(())
+    }
+
+    /// Removes all modules with the given name from the bar.
+    ///
+    /// Returns `false` if there are no matching modules.
+    pub fn remove_module(&self, name: &str) -> bool {
+        let (_, modules, popup) = self.loaded();
+
+        let removed = modules
+            .borrow_mut()
+            .extract_if(.., |module| module.module.name == name)
+            .collect::<Vec<_>>();
+
+        for module in &removed {
+            self.unload_module(module, popup);
+        }
+
+        !removed.is_empty()
+    }
+
+    /// Moves the module with the given name to `index` within the `location` container,
+    /// or to the end of the container if not set.
+    ///
+    /// Moving within the same container keeps the module's state.
+    /// Moving to a different container recreates the module from its config,
+    /// so that it picks up its new location.
+    /// If that fails, the original module is left in place.
+    ///
+    /// Returns `false` if there is no matching module.
+    pub fn move_module(
+        &self,
+        name: &str,
+        location: ModuleLocation,
+        index: Option<usize>,
+    ) -> Result<bool> {
+        let (monitor, modules, popup) = self.loaded();
+
+        let Some(current) = modules
+            .borrow()
+            .iter()
+            .position(|module| module.module.name == name)
+        else {
+            return Ok(false);
+        };
+
+        let mut module = modules.borrow()[current].clone();
+
+        if module.module.location != location {
+            let app = self.app();
+            let module_factory = self.module_factory(popup);
+            let info = self.module_info(&app, monitor, location.clone());
+
+            self.attach_container(&location, true);
+            let container = self.container(&location);
+
+            // create the new instance first, so the original is kept if this fails
+            let new_module =
+                BarModule::create(module.config.clone(), &module_factory, container, &info)?;
+
+            self.unload_module(&module, popup);
+            module = new_module;
+        }
+
+        let mut modules = modules.borrow_mut();
+        modules.remove(current);
+
+        let position = insert_position(&modules, &location, index);
+        modules.insert(position, module);
+
+        self.reorder(&modules, &location);
+
+        Ok(true)
+    }
+
     /// Closes the bar, consuming it.
     pub fn close(self) {
+        if let Inner::Loaded { modules, .. } = &self.inner {
+            for module in modules.borrow().iter() {
+                module.tasks.abort();
+            }
+        }
+
+        if let Some(autohide_state) = self.autohide_state.borrow_mut().take() {
+            autohide_state.hotspot_window.close();
+            autohide_state.hotspot_window.destroy();
+        }
+
         self.window.close();
         self.window.destroy();
     }
@@ -357,82 +575,138 @@ impl Bar {
         }
     }
 
-    /// Loads the configured modules onto a bar.
+    /// Loads the configured modules onto the bar.
+    ///
+    /// Any `existing` module with an identical config in the same location is kept,
+    /// and moved into its new position.
+    /// Remaining existing modules are removed from the bar.
     fn load_modules(
         &self,
-        instance: Rc<Bar>,
-        config: BarConfig,
+        mut existing: Vec<BarModule>,
+        configs: [Option<Vec<ModuleConfig>>; 3],
+        popup: &Rc<Popup>,
         monitor: &Monitor,
-    ) -> BarLoadResult {
-        let app = &self.window.application().expect("to exist");
-
-        macro_rules! info {
-            ($location:expr) => {
-                ModuleInfo {
-                    app,
-                    bar_position: config.position,
-                    monitor,
-                    output_name: &self.monitor_name,
-                    location: $location,
+    ) -> Vec<BarModule> {
+        let app = self.app();
+        let module_factory = self.module_factory(popup);
+
+        let locations = [
+            ModuleLocation::Left,
+            ModuleLocation::Center,
+            ModuleLocation::Right,
+        ];
+
+        let mut modules = vec![];
+
+        for (location, configs) in locations.iter().zip(configs) {
+            self.attach_container(location, configs.is_some());
+
+            let container = self.container(location);
+            let info = self.module_info(&app, monitor, location.clone());
+
+            for config in configs.unwrap_or_default() {
+                let reused = existing.iter().position(|module| {
+                    &module.module.location == location && module.config == config
+                });
+
+                if let Some(index) = reused {
+                    modules.push(existing.remove(index));
+                    continue;
+                }
+
+                let name = config.name();
+                match BarModule::create(config, &module_factory, container, &info) {
+                    Ok(module) => modules.push(module),
+                    Err(err) => error!("failed to create module {name}: {:?}", err),
                 }
-            };
+            }
         }
 
-        // popup ignores module location so can bodge this for now
-        let popup = Popup::new(
-            &info!(ModuleLocation::Left),
-            config.popup_gap,
-            config.popup_autohide,
+        for module in existing {
+            self.unload_module(&module, popup);
+        }
+
+        for location in &locations {
+            self.reorder(&modules, location);
+        }
+
+        modules
+    }
+
+    /// Removes a module's widget and popup content from the bar,
+    /// and aborts its tasks.
+    fn unload_module(&self, module: &BarModule, popup: &Popup) {
+        debug!(
+            "removing module {} (id: {})",
+            module.module.module_type, module.module.id
         );
-        let popup = Rc::new(popup);
 
-        let mut refs = vec![];
+        module.tasks.abort();
 
-        if let Some(modules) = config.start {
-            self.content.set_start_widget(Some(&self.start));
+        if let Some(popup_content) = &module.module.popup {
+            popup.unregister_content(module.module.id, popup_content);
+        }
 
-            let info = info!(ModuleLocation::Left);
-            refs.extend(add_modules(
-                &self.start,
-                modules,
-                &info,
-                &self.ironbar,
-                &instance,
-                &popup,
-            ));
+        self.container(&module.module.location)
+            .remove(&module.revealer());
+    }
+
+    /// Reorders the widgets in a container to match the order of `modules`.
+    fn reorder(&self, modules: &[BarModule], location: &ModuleLocation) {
+        let container = self.container(location);
+        let mut previous = None;
+
+        for module in modules
+            .iter()
+            .filter(|module| &module.module.location == location)
+        {
+            let revealer = module.revealer();
+            container.reorder_child_after(&revealer, previous.as_ref());
+            previous = Some(revealer);
         }
+    }
 
-        if let Some(modules) = config.center {
-            self.content.set_center_widget(Some(&self.center));
+    fn module_factory(&self, popup: &Rc<Popup>) -> AnyModuleFactory {
+        BarModuleFactory::new(self.ironbar.clone(), Rc::new(self.clone()), popup.clone()).into()
+    }
 
-            let info = info!(ModuleLocation::Center);
-            refs.extend(add_modules(
-                &self.center,
-                modules,
-                &info,
-                &self.ironbar,
-                &instance,
-                &popup,
-            ));
+    fn module_info<'a>(
+        &'a self,
+        app: &'a Application,
+        monitor: &'a Monitor,
+        location: ModuleLocation,
+    ) -> ModuleInfo<'a> {
+        ModuleInfo {
+            app,
+            bar_position: self.position,
+            monitor,
+            output_name: &self.monitor_name,
+            location,
         }
+    }
 
-        if let Some(modules) = config.end {
-            self.content.set_end_widget(Some(&self.end));
+    fn app(&self) -> Application {
+        self.window.application().expect("to exist")
+    }
 
-            let info = info!(ModuleLocation::Right);
-            refs.extend(add_modules(
-                &self.end,
-                modules,
-                &info,
-                &self.ironbar,
-                &instance,
-                &popup,
-            ));
+    /// Sets or unsets the container for a module location
+    /// as the content's start, center or end widget.
+    fn attach_container(&self, location: &ModuleLocation, attach: bool) {
+        let container = attach.then(|| self.container(location));
+
+        match location {
+            ModuleLocation::Left => self.content.set_start_widget(container),
+            ModuleLocation::Center => self.content.set_center_widget(container),
+            ModuleLocation::Right => self.content.set_end_widget(container),
         }
+    }
 
-        BarLoadResult {
-            popup,
-            module_refs: refs,
+    /// Gets the container box for a module location.
+    fn container(&self, location: &ModuleLocation) -> &gtk::Box {
+        match location {
+            ModuleLocation::Left => &self.start,
+            ModuleLocation::Center => &self.center,
+            ModuleLocation::Right => &self.end,
         }
     }
 
@@ -460,6 +734,10 @@ impl Bar {
         &self.monitor_name
     }
 
+    pub fn position(&self) -> BarPosition {
+        self.position
+    }
+
     pub fn popup(&self) -> Rc<Popup> {
         match &self.inner {
             Inner::New { .. } => {
@@ -478,6 +756,12 @@ impl Bar {
         self.window.set_visible(visible);
     }
 
+    /// Gets the size of the exclusive zone reserved by the bar.
+    /// This is `0` if the bar is not exclusive.
+    pub fn exclusive_zone(&self) -> i32 {
+        self.window.exclusive_zone()
+    }
+
     pub fn set_exclusive(&self, exclusive: bool) {
         if exclusive {
             self.window.auto_exclusive_zone_enable();
@@ -501,12 +785,27 @@ impl Bar {
         }
     }
 
-    pub fn modules(&self) -> &[ModuleRef] {
+    pub fn modules(&self) -> Vec<ModuleRef> {
+        let (_, modules, _) = self.loaded();
+
+        modules
+            .borrow()
+            .iter()
+            .map(|module| module.module.clone())
+            .collect()
+    }
+
+    fn loaded(&self) -> (&Monitor, &Rc<RefCell<Vec<BarModule>>>, &Rc<Popup>) {
         match &self.inner {
             Inner::New { .. } => {
                 panic!("Attempted to get modules of uninitialized bar. This is a serious bug!")
             }
-            Inner::Loaded { module_refs, .. } => module_refs,
+            Inner::Loaded {
+                monitor,
+                modules,
+                popup,
+                ..
+            } => (monitor, modules, popup),
         }
     }
 }
@@ -523,34 +822,30 @@ fn create_container(name: &str, orientation: Orientation) -> gtk::Box {
     container
 }
 
-#[derive(Debug)]
-struct BarLoadResult {
-    popup: Rc<Popup>,
-    module_refs: Vec<ModuleRef>,
+/// Gets the position in `modules` at which to insert a module
+/// so that it is at `index` within the `location` container,
+/// or at the end of the container if not set.
+fn insert_position(
+    modules: &[BarModule],
+    location: &ModuleLocation,
+    index: Option<usize>,
+) -> usize {
+    let in_location = modules
+        .iter()
+        .enumerate()
+        .filter(|(_, module)| &module.module.location == location)
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    index
+        .and_then(|index| in_location.get(index).copied())
+        .or_else(|| in_location.last().map(|i| i + 1))
+        .unwrap_or(modules.len())
 }
 
-/// Adds modules into a provided GTK box,
-/// which should be one of its left, center or right containers.
-fn add_modules(
-    content: &gtk::Box,
-    modules: Vec<ModuleConfig>,
-    info: &ModuleInfo,
-    ironbar: &Rc<Ironbar>,
-    slf: &Rc<Bar>,
-    popup: &Rc<Popup>,
-) -> Vec<ModuleRef> {
-    let module_factory = BarModuleFactory::new(ironbar.clone(), slf.clone(), popup.clone()).into();
-
-    let mut results = vec![];
-    for config in modules {
-        let name = config.name();
-        match config.create(&module_factory, content, info) {
-            Ok(res) => results.push(res),
-            Err(err) => error!("failed to create module {name}: {:?}", err),
-        }
-    }
-
-    results
+/// Takes the start, center and end module configs out of a bar config.
+fn take_modules(config: &mut BarConfig) -> [Option<Vec<ModuleConfig>>; 3] {
+    [config.start.take(), config.center.take(), config.end.take()]
 }
 
 pub fn create_bar(
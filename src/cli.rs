@@ -1,8 +1,13 @@
 use crate::config::ConfigLocation;
+#[cfg(feature = "config")]
+use crate::config::validate::Report;
 use crate::error::ExitCode;
-use crate::ipc::{Command, Response};
+use crate::ipc::{Command, Event, Response};
 use clap::{Parser, ValueEnum};
+use color_eyre::Result;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::BufRead;
 use std::process::exit;
 
 #[derive(Parser, Debug, Serialize, Deserialize)]
@@ -34,6 +39,11 @@ pub struct Args {
     #[arg(short('t'), long, env = "IRONBAR_CSS")]
     pub theme: Option<ConfigLocation>,
 
+    /// Name of the instance to start or send commands to.
+    /// Each instance has its own IPC socket.
+    #[arg(short('i'), long, env = "IRONBAR_INSTANCE", value_parser = crate::parse_instance)]
+    pub instance: Option<String>,
+
     /// Format to output the response as.
     #[arg(short, long)]
     pub format: Option<Format>,
@@ -74,16 +84,99 @@ impl From<Shell> for clap_complete::Shell {
     }
 }
 
+/// A single command within a batch.
+#[derive(Parser, Debug)]
+#[command(no_binary_name(true))]
+struct BatchLine {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Reads a batch of commands, one per line.
+///
+/// Each line is either CLI arguments or a JSON command.
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_batch(reader: impl BufRead) -> Result<Vec<Command>> {
+    let mut commands = vec![];
+
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let command = if line.starts_with('{') {
+            serde_json::from_str(line).map_err(color_eyre::Report::new)
+        } else {
+            split_args(line).and_then(|args| {
+                BatchLine::try_parse_from(args)
+                    .map(|line| line.command)
+                    .map_err(|err| color_eyre::Report::msg(err.render().to_string()))
+            })
+        };
+
+        commands.push(
+            command.map_err(|err| err.wrap_err(format!("Invalid command on line {}", i + 1)))?,
+        );
+    }
+
+    Ok(commands)
+}
+
+/// Splits a line into arguments on whitespace,
+/// keeping quoted sections together.
+fn split_args(line: &str) -> Result<Vec<String>> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+
+    let mut chars = line.chars();
+    while let Some(char) = chars.next() {
+        match (quote, char) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+                in_arg = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(char);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(color_eyre::Report::msg("Unterminated quote"));
+    }
+
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
 pub fn handle_response(response: Response, format: Format) {
-    let is_err = matches!(response, Response::Err { .. });
+    let is_err = response.is_err();
 
     match format {
-        Format::Plain => match response {
-            Response::Ok => println!("ok"),
-            Response::OkValue { value } => println!("{value}"),
-            Response::Multi { values } => println!("{}", values.join("\n")),
-            Response::Err { message } => eprintln!("error\n{}", message.unwrap_or_default()),
-        },
+        Format::Plain => print_plain(response),
         Format::Json => println!(
             "{}",
             serde_json::to_string(&response).expect("to be valid json")
@@ -94,3 +187,191 @@ pub fn handle_response(response: Response, format: Format) {
         exit(ExitCode::IpcResponseError as i32)
     }
 }
+
+fn print_plain(response: Response) {
+    match response {
+        Response::Ok => println!("ok"),
+        Response::OkValue { value } => println!("{value}"),
+        Response::Multi { values } => println!("{}", values.join("\n")),
+        Response::Json { value } => println!("{}", format_plain(&value)),
+        Response::Batch { responses } => responses.into_iter().for_each(print_plain),
+        Response::Err { message } => eprintln!("error\n{}", message.unwrap_or_default()),
+    }
+}
+
+/// Formats a JSON value for plain output.
+///
+/// Scalars are printed as-is,
+/// arrays of scalars one per line,
+/// and arrays of flat objects as a table.
+/// Anything more deeply nested falls back to pretty-printed JSON.
+fn format_plain(value: &Value) -> String {
+    match value {
+        Value::Array(items) if items.iter().all(is_scalar) => items
+            .iter()
+            .map(format_scalar)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Array(items) if items.iter().all(is_flat_object) => format_table(items),
+        Value::Array(_) | Value::Object(_) => {
+            serde_json::to_string_pretty(value).expect("to be valid json")
+        }
+        _ => format_scalar(value),
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !matches!(value, Value::Array(_) | Value::Object(_))
+}
+
+fn is_flat_object(value: &Value) -> bool {
+    matches!(value, Value::Object(map) if map.values().all(is_scalar))
+}
+
+fn format_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(str) => str.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Formats an array of flat objects as a table,
+/// with one column per key in order of first appearance.
+fn format_table(rows: &[Value]) -> String {
+    let mut columns: Vec<&str> = vec![];
+    for row in rows.iter().filter_map(Value::as_object) {
+        for key in row.keys() {
+            if !columns.contains(&key.as_str()) {
+                columns.push(key);
+            }
+        }
+    }
+
+    let header = columns.iter().map(|col| col.to_uppercase()).collect();
+    let cells = rows.iter().map(|row| {
+        columns
+            .iter()
+            .map(|col| row.get(col).map(format_scalar).unwrap_or_default())
+            .collect::<Vec<_>>()
+    });
+
+    let table = std::iter::once(header).chain(cells).collect::<Vec<_>>();
+
+    let widths = (0..columns.len())
+        .map(|i| {
+            table
+                .iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    table
+        .iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(feature = "config")]
+pub fn handle_config_report(report: Report, format: Format) {
+    let is_valid = report.is_valid();
+
+    match format {
+        Format::Plain if is_valid => println!("ok"),
+        Format::Plain => eprintln!("{report}"),
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string(&report).expect("to be valid json")
+        ),
+    }
+
+    if !is_valid {
+        exit(ExitCode::InvalidConfig as i32)
+    }
+}
+
+pub fn handle_event(event: Event, format: Format) {
+    match format {
+        Format::Plain => println!("{event}"),
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string(&event).expect("to be valid json")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_plain_scalars() {
+        assert_eq!(format_plain(&json!("hello")), "hello");
+        assert_eq!(format_plain(&json!(true)), "true");
+        assert_eq!(format_plain(&json!(null)), "");
+        assert_eq!(format_plain(&json!(["a", 1])), "a\n1");
+    }
+
+    #[test]
+    fn test_format_plain_table() {
+        let value = json!([
+            { "key": "music", "type": "namespace" },
+            { "key": "foo", "type": "variable", "value": "bar" },
+        ]);
+
+        assert_eq!(
+            format_plain(&value),
+            "KEY    TYPE       VALUE\nmusic  namespace\nfoo    variable   bar"
+        );
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(
+            split_args(r#"var set foo "hello world""#).expect("to split"),
+            ["var", "set", "foo", "hello world"]
+        );
+        assert_eq!(
+            split_args(r#"var set 'a "b"' c\ d """#).expect("to split"),
+            ["var", "set", r#"a "b""#, "c d", ""]
+        );
+        assert!(split_args("var set 'foo").is_err());
+    }
+
+    #[test]
+    fn test_read_batch() {
+        let input = "var set foo bar\n\n# comment\n{\"command\":\"ping\"}\n";
+        let commands = read_batch(input.as_bytes()).expect("to be valid");
+
+        assert!(matches!(
+            commands.as_slice(),
+            [
+                Command::Var(crate::ipc::IronvarCommand::Set { .. }),
+                Command::Ping
+            ]
+        ));
+
+        assert!(read_batch("var nope".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_format_plain_nested() {
+        let value = json!({ "a": [1, 2] });
+        assert_eq!(
+            format_plain(&value),
+            serde_json::to_string_pretty(&value).expect("to be valid json")
+        );
+    }
+}
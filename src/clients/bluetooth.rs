@@ -49,6 +49,92 @@ pub struct BluetoothDevice {
     pub battery_percent: Option<u8>,
 }
 
+impl BluetoothState {
+    /// Gets the name of the state, as exposed over ironvars.
+    #[cfg(feature = "ipc")]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Enabling => "enabling",
+            Self::Enabled { .. } => "enabled",
+            Self::Disabled => "disabled",
+            Self::Disabling => "disabling",
+            Self::NotFound => "not_found",
+        }
+    }
+}
+
+impl BluetoothDeviceStatus {
+    /// Gets the name of the status, as exposed over ironvars.
+    #[cfg(feature = "ipc")]
+    fn name(self) -> &'static str {
+        match self {
+            Self::Connecting => "connecting",
+            Self::Connected => "connected",
+            Self::Disconnecting => "disconnecting",
+            Self::Disconnected => "disconnected",
+        }
+    }
+}
+
+/// Registers the `bluetooth` ironvar namespace,
+/// keeping it updated with the adapter state and known devices.
+///
+/// Devices are exposed by index inside the `devices` namespace,
+/// in the same order as the bluetooth module lists them.
+#[cfg(feature = "ipc")]
+pub fn register_namespace(client: &Client) {
+    use crate::ironvar::{NamespaceState, StateNamespace};
+
+    let namespace = std::sync::Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("bluetooth", namespace.clone());
+
+    let mut rx = client.subscribe();
+    spawn(async move {
+        loop {
+            let mut state = NamespaceState::default();
+
+            {
+                let bluetooth_state = rx.borrow_and_update();
+                state.insert("state", bluetooth_state.name());
+
+                let devices = match &*bluetooth_state {
+                    BluetoothState::Enabled { devices } => devices.as_slice(),
+                    _ => &[],
+                };
+
+                state.insert("device_count", devices.len());
+                state.insert(
+                    "connected_count",
+                    devices
+                        .iter()
+                        .filter(|device| device.status == BluetoothDeviceStatus::Connected)
+                        .count(),
+                );
+
+                let mut devices_state = NamespaceState::default();
+                for (i, device) in devices.iter().enumerate() {
+                    let mut device_state = NamespaceState::default();
+                    device_state.insert("address", device.address);
+                    device_state.insert("alias", &device.alias);
+                    device_state.insert("status", device.status.name());
+                    device_state.insert_opt("icon", device.icon.as_ref());
+                    device_state.insert_opt("battery_percent", device.battery_percent);
+
+                    devices_state.insert_namespace(&i.to_string(), device_state);
+                }
+
+                state.insert_namespace("devices", devices_state);
+            }
+
+            namespace.set(state);
+
+            if rx.changed().await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
 #[derive(Debug, Clone)]
 pub struct Client {
     session: bluer::Session,
@@ -145,6 +145,18 @@ impl Client {
             tx.send_spawn(ClipboardEvent::Remove(id));
         }
     }
+
+    /// Removes all items from the cache.
+    pub fn clear(&self) {
+        let ids = lock!(self.cache)
+            .iter()
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in ids {
+            self.remove(id);
+        }
+    }
 }
 
 /// Shared clipboard item cache.
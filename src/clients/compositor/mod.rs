@@ -254,6 +254,27 @@ pub trait KeyboardLayoutClient: Debug + Send + Sync {
 #[cfg(feature = "keyboard")]
 register_fallible_client!(dyn KeyboardLayoutClient, keyboard_layout);
 
+/// Registers the `keyboard_layout` ironvar namespace,
+/// keeping it updated with the active layout.
+#[cfg(all(feature = "keyboard", feature = "ipc"))]
+pub fn register_keyboard_layout_namespace(client: &dyn KeyboardLayoutClient) {
+    use crate::ironvar::{NamespaceState, StateNamespace};
+    use crate::{Ironbar, spawn};
+
+    let namespace = Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("keyboard_layout", namespace.clone());
+
+    let mut rx = client.subscribe();
+    spawn(async move {
+        while let Ok(KeyboardLayoutUpdate(layout)) = rx.recv().await {
+            let mut state = NamespaceState::default();
+            state.insert("layout", layout);
+
+            namespace.set(state);
+        }
+    });
+}
+
 #[cfg(feature = "bindmode")]
 pub trait BindModeClient: Debug + Send + Sync {
     /// Add a callback for bindmode updates.
@@ -262,3 +283,27 @@ pub trait BindModeClient: Debug + Send + Sync {
 
 #[cfg(feature = "bindmode")]
 register_fallible_client!(dyn BindModeClient, bindmode);
+
+/// Registers the `bindmode` ironvar namespace,
+/// keeping it updated with the active binding mode.
+#[cfg(all(feature = "bindmode", feature = "ipc"))]
+pub fn register_bindmode_namespace(client: &dyn BindModeClient) -> Result<()> {
+    use crate::ironvar::{NamespaceState, StateNamespace};
+    use crate::{Ironbar, spawn};
+
+    let mut rx = client.subscribe()?;
+
+    let namespace = Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("bindmode", namespace.clone());
+
+    spawn(async move {
+        while let Ok(BindModeUpdate { name, .. }) = rx.recv().await {
+            let mut state = NamespaceState::default();
+            state.insert("mode", name);
+
+            namespace.set(state);
+        }
+    });
+
+    Ok(())
+}
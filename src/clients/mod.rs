@@ -92,7 +92,14 @@ impl Clients {
 
     pub fn wayland(&mut self) -> Arc<wayland::Client> {
         self.wayland
-            .get_or_insert_with(|| Arc::new(wayland::Client::new()))
+            .get_or_insert_with(|| {
+                let client = Arc::new(wayland::Client::new());
+
+                #[cfg(all(feature = "ipc", any(feature = "focused", feature = "launcher")))]
+                wayland::register_focused_namespace(&client);
+
+                client
+            })
             .clone()
     }
 
@@ -137,6 +144,10 @@ impl Clients {
             keyboard_layout.clone()
         } else {
             let client = compositor::Compositor::create_keyboard_layout_client(self)?;
+
+            #[cfg(feature = "ipc")]
+            compositor::register_keyboard_layout_namespace(&*client);
+
             self.keyboard_layout.replace(client.clone());
             client
         };
@@ -150,6 +161,12 @@ impl Clients {
             client.clone()
         } else {
             let client = compositor::Compositor::create_bindmode_client(self)?;
+
+            #[cfg(feature = "ipc")]
+            if let Err(err) = compositor::register_bindmode_namespace(&*client) {
+                tracing::error!("Failed to register bindmode namespace: {err}");
+            }
+
             self.bindmode.replace(client.clone());
             client
         };
@@ -204,7 +221,21 @@ impl Clients {
     pub fn music(&mut self, client_type: music::ClientType) -> Arc<dyn music::MusicClient> {
         self.music
             .entry(client_type.clone())
-            .or_insert_with(|| music::create_client(client_type))
+            .or_insert_with(|| {
+                // only the MPRIS client provides the namespace,
+                // so that several players do not overwrite each other
+                #[cfg(all(feature = "ipc", feature = "music+mpris"))]
+                let is_mpris = matches!(client_type, music::ClientType::Mpris);
+
+                let client = music::create_client(client_type);
+
+                #[cfg(all(feature = "ipc", feature = "music+mpris"))]
+                if is_mpris {
+                    music::register_namespace(&client);
+                }
+
+                client
+            })
             .clone()
     }
 
@@ -214,6 +245,10 @@ impl Clients {
             Ok(client.clone())
         } else {
             let client = await_sync(async move { networkmanager::create_client().await })?;
+
+            #[cfg(feature = "ipc")]
+            networkmanager::register_namespace(&client);
+
             self.network_manager = Some(client.clone());
             Ok(client)
         }
@@ -226,6 +261,9 @@ impl Clients {
         } else {
             let client = await_sync(async { swaync::Client::new().await })?;
             let client = Arc::new(client);
+
+            #[cfg(feature = "ipc")]
+            swaync::register_namespace(&client);
             self.notifications.replace(client.clone());
             client
         };
@@ -280,7 +318,14 @@ impl Clients {
     #[cfg(feature = "volume")]
     pub fn volume(&mut self) -> Arc<volume::Client> {
         self.volume
-            .get_or_insert_with(volume::create_client)
+            .get_or_insert_with(|| {
+                let client = volume::create_client();
+
+                #[cfg(feature = "ipc")]
+                volume::register_namespace(&client);
+
+                client
+            })
             .clone()
     }
 
@@ -291,6 +336,9 @@ impl Clients {
         } else {
             let client = await_sync(async { bluetooth::Client::new().await })?;
             let client = Arc::new(client);
+
+            #[cfg(feature = "ipc")]
+            bluetooth::register_namespace(&client);
             self.bluetooth.replace(client.clone());
             client
         };
@@ -299,6 +347,62 @@ impl Clients {
     }
 }
 
+#[cfg(feature = "ipc")]
+impl Clients {
+    /// Creates the client which provides the ironvar namespace `name`,
+    /// if it does not already exist.
+    pub fn init_namespace(&mut self, name: &str) -> Result<()> {
+        match name {
+            "focused" => {
+                self.wayland();
+            }
+            #[cfg(feature = "music+mpris")]
+            "music" => {
+                self.music(music::ClientType::Mpris);
+            }
+            #[cfg(feature = "volume")]
+            "volume" => {
+                self.volume();
+            }
+            #[cfg(feature = "network_manager")]
+            "network_manager" => {
+                self.network_manager()?;
+            }
+            #[cfg(feature = "bluetooth")]
+            "bluetooth" => {
+                self.bluetooth()?;
+            }
+            #[cfg(feature = "notifications")]
+            "notifications" => {
+                self.notifications()?;
+            }
+            #[cfg(feature = "keyboard")]
+            "keyboard_layout" => {
+                self.keyboard_layout()?;
+            }
+            #[cfg(feature = "bindmode")]
+            "bindmode" => {
+                self.bindmode()?;
+            }
+            #[cfg(feature = "sys_info")]
+            "sysinfo" => {
+                self.sys_info();
+            }
+            #[cfg(feature = "battery")]
+            "upower" => {
+                self.upower()?;
+            }
+            _ => {
+                return Err(color_eyre::Report::msg(format!(
+                    "Unknown namespace '{name}'"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Types implementing this trait
 /// indicate that they provide a singleton client instance of type `T`.
 pub trait ProvidesClient<T: ?Sized> {
@@ -331,7 +435,9 @@ macro_rules! register_client {
             TSend: Clone,
         {
             fn provide(&self) -> std::sync::Arc<$ty> {
-                self.ironbar.clients.borrow_mut().$method()
+                $crate::modules::ModuleTasks::untracked(|| {
+                    self.ironbar.clients.borrow_mut().$method()
+                })
             }
         }
     };
@@ -354,7 +460,9 @@ macro_rules! register_fallible_client {
             TSend: Clone,
         {
             fn try_provide(&self) -> color_eyre::Result<std::sync::Arc<$ty>> {
-                self.ironbar.clients.borrow_mut().$method()
+                $crate::modules::ModuleTasks::untracked(|| {
+                    self.ironbar.clients.borrow_mut().$method()
+                })
             }
         }
     };
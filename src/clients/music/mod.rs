@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -41,6 +41,18 @@ pub enum PlayerState {
     Paused,
 }
 
+impl Display for PlayerState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let state = match self {
+            Self::Stopped => "stopped",
+            Self::Playing => "playing",
+            Self::Paused => "paused",
+        };
+
+        write!(f, "{state}")
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Status {
     pub state: PlayerState,
@@ -77,6 +89,48 @@ pub enum ClientType {
     Mpris,
 }
 
+/// Registers the `music` ironvar namespace,
+/// keeping it updated with the current track and player state.
+///
+/// This should only be called once, for the MPRIS client.
+#[cfg(all(feature = "ipc", feature = "music+mpris"))]
+pub fn register_namespace(client: &Arc<dyn MusicClient>) {
+    use crate::ironvar::{NamespaceState, StateNamespace};
+    use crate::{Ironbar, spawn};
+
+    let namespace = Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("music", namespace.clone());
+
+    let mut rx = client.subscribe_change();
+    spawn(async move {
+        while let Ok(update) = rx.recv().await {
+            match update {
+                PlayerUpdate::Update(track, status) => namespace.update(|state| {
+                    let track = Option::as_ref(&track);
+
+                    state.insert_opt("title", track.and_then(|t| t.title.as_ref()));
+                    state.insert_opt("album", track.and_then(|t| t.album.as_ref()));
+                    state.insert_opt("artist", track.and_then(|t| t.artist.as_ref()));
+                    state.insert_opt("date", track.and_then(|t| t.date.as_ref()));
+                    state.insert_opt("genre", track.and_then(|t| t.genre.as_ref()));
+                    state.insert_opt("cover_path", track.and_then(|t| t.cover_path.as_ref()));
+                    state.insert_opt("disc", track.and_then(|t| t.disc));
+                    state.insert_opt("track", track.and_then(|t| t.track));
+
+                    state.insert("state", status.state);
+                    state.insert_opt("volume_percent", status.volume_percent);
+                    state.insert("playlist_position", status.playlist_position);
+                    state.insert("playlist_length", status.playlist_length);
+                }),
+                PlayerUpdate::ProgressTick(tick) => namespace.update(|state| {
+                    state.insert_opt("elapsed", tick.elapsed.map(|d| d.as_secs()));
+                    state.insert_opt("duration", tick.duration.map(|d| d.as_secs()));
+                }),
+            }
+        }
+    });
+}
+
 pub fn create_client(client_type: ClientType) -> Arc<dyn MusicClient> {
     match client_type {
         #[cfg(feature = "music+mpd")]
@@ -132,6 +132,58 @@ impl Client {
     }
 }
 
+impl ClientState {
+    /// Gets the name of the state, as exposed over ironvars.
+    #[cfg(feature = "ipc")]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::WiredConnected => "wired_connected",
+            Self::WifiConnected => "wifi_connected",
+            Self::CellularConnected => "cellular_connected",
+            Self::VpnConnected => "vpn_connected",
+            Self::WifiDisconnected => "wifi_disconnected",
+            Self::Offline => "offline",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Whether the state represents an active connection.
+    #[cfg(feature = "ipc")]
+    fn is_connected(&self) -> bool {
+        matches!(
+            self,
+            Self::WiredConnected
+                | Self::WifiConnected
+                | Self::CellularConnected
+                | Self::VpnConnected
+        )
+    }
+}
+
+/// Registers the `network_manager` ironvar namespace,
+/// keeping it updated with the current connection state.
+#[cfg(feature = "ipc")]
+pub fn register_namespace(client: &Client) {
+    use crate::Ironbar;
+    use crate::ironvar::{NamespaceState, StateNamespace};
+    use futures_lite::StreamExt;
+    use futures_signals::signal::SignalExt;
+
+    let namespace = Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("network_manager", namespace.clone());
+
+    let mut stream = client.subscribe().to_stream();
+    spawn(async move {
+        while let Some(client_state) = stream.next().await {
+            let mut state = NamespaceState::default();
+            state.insert("state", client_state.name());
+            state.insert("connected", client_state.is_connected());
+
+            namespace.set(state);
+        }
+    });
+}
+
 pub async fn create_client() -> Result<Arc<Client>> {
     let client = Arc::new(Client::new().await?);
     {
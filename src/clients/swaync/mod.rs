@@ -89,4 +89,39 @@ impl Client {
     }
 }
 
+/// Registers the `notifications` ironvar namespace,
+/// keeping it updated with the notification count and do-not-disturb state.
+#[cfg(feature = "ipc")]
+pub fn register_namespace(client: &std::sync::Arc<Client>) {
+    use crate::Ironbar;
+    use crate::ironvar::{NamespaceState, StateNamespace};
+
+    let namespace = std::sync::Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("notifications", namespace.clone());
+
+    let client = client.clone();
+    let mut rx = client.subscribe();
+
+    spawn(async move {
+        let set = |ev: Event| {
+            let mut state = NamespaceState::default();
+            state.insert("count", ev.count);
+            state.insert("dnd", ev.dnd);
+            state.insert("cc_open", ev.cc_open);
+            state.insert("inhibited", ev.inhibited);
+
+            namespace.set(state);
+        };
+
+        match client.state().await {
+            Ok(ev) => set(ev),
+            Err(err) => error!("{err:?}"),
+        }
+
+        while let Ok(ev) = rx.recv().await {
+            set(ev);
+        }
+    });
+}
+
 register_fallible_client!(Client, notifications);
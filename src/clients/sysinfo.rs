@@ -600,6 +600,15 @@ impl Namespace for Client {
             TokenType::Uptime => None,
         }
     }
+
+    /// Refreshes the values which are not measured over an interval.
+    /// CPU usage, disk and network rates are left to the `sys_info` module,
+    /// as refreshing them here would shorten its measurement window.
+    fn refresh(&self) {
+        self.refresh_memory();
+        self.refresh_temps();
+        self.refresh_load_average();
+    }
 }
 
 #[cfg(feature = "ipc")]
@@ -159,6 +159,52 @@ pub fn create_client() -> Arc<Client> {
     client
 }
 
+/// Registers the `volume` ironvar namespace,
+/// keeping it updated with the state of the default sink.
+#[cfg(feature = "ipc")]
+pub fn register_namespace(client: &Arc<Client>) {
+    use crate::ironvar::{NamespaceState, StateNamespace};
+    use crate::{Ironbar, spawn};
+    use tokio::sync::broadcast::error::RecvError;
+
+    let namespace = Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("volume", namespace.clone());
+
+    let state = |client: &Client| {
+        let mut state = NamespaceState::default();
+
+        let sinks = lock!(client.data.sinks);
+        state.insert("sink_count", sinks.len());
+        state.insert("input_count", lock!(client.data.sink_inputs).len());
+
+        if let Some(sink) = sinks.iter().find(|sink| sink.active) {
+            let mut default_sink = NamespaceState::default();
+            default_sink.insert("name", &sink.name);
+            default_sink.insert("description", &sink.description);
+            default_sink.insert("volume", sink.volume.percent());
+            default_sink.insert("muted", sink.muted);
+
+            state.insert_namespace("default_sink", default_sink);
+        }
+
+        state
+    };
+
+    let client = client.clone();
+    let mut rx = client.subscribe();
+
+    spawn(async move {
+        namespace.set(state(&client));
+
+        loop {
+            match rx.recv().await {
+                Ok(_) | Err(RecvError::Lagged(_)) => namespace.set(state(&client)),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
 fn on_state_change(context: &Arc<Mutex<Context>>, data: &Data, tx: &broadcast::Sender<Event>) {
     let Ok(state) = context.try_lock().map(|lock| lock.get_state()) else {
         return;
@@ -33,6 +33,8 @@ cfg_if! {
         use crate::{delegate_foreign_toplevel_handle, delegate_foreign_toplevel_manager};
         use wlr_foreign_toplevel::manager::ToplevelManagerState;
         pub use wlr_foreign_toplevel::{ToplevelEvent, ToplevelHandle, ToplevelInfo};
+        #[cfg(feature = "ipc")]
+        pub use wlr_foreign_toplevel::register_focused_namespace;
 
     }
 }
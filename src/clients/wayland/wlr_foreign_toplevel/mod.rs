@@ -51,6 +51,56 @@ impl Client {
     }
 }
 
+/// Registers the `focused` ironvar namespace,
+/// keeping it updated with the title and app ID of the focused window.
+#[cfg(feature = "ipc")]
+pub fn register_focused_namespace(client: &std::sync::Arc<Client>) {
+    use crate::ironvar::{NamespaceState, StateNamespace};
+    use crate::{Ironbar, spawn};
+
+    let namespace = std::sync::Arc::new(StateNamespace::default());
+    Ironbar::variable_manager().register_namespace("focused", namespace.clone());
+
+    let set = move |info: Option<&ToplevelInfo>| {
+        let mut state = NamespaceState::default();
+        if let Some(info) = info {
+            state.insert("title", &info.title);
+            state.insert("app_id", &info.app_id);
+        }
+
+        namespace.set(state);
+    };
+
+    let client = client.clone();
+    spawn(async move {
+        let mut rx = client.subscribe_toplevels();
+
+        let mut current = client
+            .toplevel_info_all()
+            .into_iter()
+            .find(|info| info.focused);
+        set(current.as_ref());
+
+        while let Ok(event) = rx.recv().await {
+            match event {
+                ToplevelEvent::Update(info) if info.focused => {
+                    set(Some(&info));
+                    current = Some(info);
+                }
+                ToplevelEvent::Update(info) | ToplevelEvent::Remove(info)
+                    if current
+                        .as_ref()
+                        .is_some_and(|current| current.id == info.id) =>
+                {
+                    set(None);
+                    current = None;
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
 impl ToplevelManagerHandler for Environment {
     fn toplevel(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>) {
         debug!("Manager received new handle");
@@ -19,7 +19,7 @@ use tracing::trace;
 /// For information on the Script type, and embedding scripts in strings,
 /// see [here](script).
 /// For information on styling, please see the [styling guide](styling-guide).
-#[derive(Debug, Default, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub struct CommonConfig {
     /// Sets the unique widget name,
@@ -206,9 +206,18 @@ pub struct CommonConfig {
     /// Prevents the popup from opening on-click for this widget.
     #[serde(default)]
     pub disable_popup: bool,
+
+    /// The name of a [template](configuration-guide#templates) to base this module on.
+    /// Any options set on the module override those set by the template.
+    ///
+    /// Templates are resolved when the config is loaded.
+    ///
+    /// **Default**: `null`
+    #[serde(skip_deserializing)]
+    pub template: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum TransitionType {
@@ -218,7 +227,7 @@ pub enum TransitionType {
     SlideEnd,
 }
 
-#[derive(Debug, Default, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum ModuleOrientation {
@@ -247,7 +256,7 @@ impl From<ModuleOrientation> for Orientation {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Clone, Copy)]
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum ModuleJustification {
@@ -285,9 +294,35 @@ impl TransitionType {
     }
 }
 
+/// Information about the module an event script is attached to.
+/// This is passed to the script as environment variables.
+#[derive(Debug, Default, Clone)]
+pub struct EventContext {
+    /// The module's instance name, or its type if it has none.
+    pub module_name: String,
+    pub bar_name: String,
+    /// The name of the output the bar is on.
+    pub monitor: String,
+}
+
+impl EventContext {
+    /// Gets the environment variables for an event script,
+    /// including any event-specific `extra` variables.
+    fn env(&self, extra: &[(&'static str, String)]) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("IRONBAR_MODULE_NAME", self.module_name.clone()),
+            ("IRONBAR_BAR_NAME", self.bar_name.clone()),
+            ("IRONBAR_MONITOR", self.monitor.clone()),
+        ];
+
+        env.extend_from_slice(extra);
+        env
+    }
+}
+
 impl CommonConfig {
     /// Configures the module's container according to the common config options.
-    pub fn install_events<W>(mut self, container: &W, revealer: &Revealer)
+    pub fn install_events<W>(mut self, container: &W, revealer: &Revealer, context: &EventContext)
     where
         W: IsA<Widget>,
     {
@@ -305,18 +340,21 @@ impl CommonConfig {
                 let double = double.map(Script::new_polling);
 
                 if single.is_some() || double.is_some() {
+                    let env = context.env(&[("IRONBAR_BUTTON", button_name.to_string())]);
+                    let double_env = env.clone();
+
                     container.connect_pressed_with_double_click(
                         button,
                         move || {
                             if let Some(script) = &single {
                                 trace!("Running on-click script: {}", button_name);
-                                script.run_as_oneshot(None);
+                                script.run_as_oneshot_with_env(None, env.clone());
                             }
                         },
                         double.map(|script| {
                             move || {
                                 trace!("Running on-double-click script: {}", button_name);
-                                script.run_as_oneshot(None);
+                                script.run_as_oneshot_with_env(None, double_env.clone());
                             }
                         }),
                     );
@@ -350,6 +388,8 @@ impl CommonConfig {
         let scroll_speed = self.smooth_scroll_speed.unwrap_or(1.0);
         let curr_scroll = Cell::new(0.0);
 
+        let scroll_context = context.clone();
+
         event_controller.connect_scroll(move |_, _dx, dy| {
             let script = if dy > 0.0 {
                 scroll_down_script.as_ref()
@@ -384,7 +424,8 @@ impl CommonConfig {
                     if dy > 0.0 { "down" } else { "up" }
                 );
 
-                script.run_as_oneshot(None);
+                let env = scroll_context.env(&[("IRONBAR_SCROLL_DELTA", dy.to_string())]);
+                script.run_as_oneshot_with_env(None, env);
             }
 
             Propagation::Proceed
@@ -395,14 +436,16 @@ impl CommonConfig {
         let event_controller = EventControllerMotion::new();
 
         if let Some(script) = self.on_mouse_enter.map(Script::new_polling) {
+            let env = context.env(&[]);
             event_controller.connect_enter(move |_, _, _| {
-                script.run_as_oneshot(None);
+                script.run_as_oneshot_with_env(None, env.clone());
             });
         }
 
         if let Some(script) = self.on_mouse_exit.map(Script::new_polling) {
+            let env = context.env(&[]);
             event_controller.connect_leave(move |_| {
-                script.run_as_oneshot(None);
+                script.run_as_oneshot_with_env(None, env.clone());
             });
         }
 
@@ -78,6 +78,22 @@ pub fn schema_layer(_generator: &mut schemars::SchemaGenerator) -> schemars::Sch
     })
 }
 
+/// Templates are partial module configs,
+/// so any module option can be set and none are required, including `type`.
+#[cfg(feature = "extras")]
+pub fn schema_templates(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": "object",
+        "additionalProperties": {
+            "type": "object",
+            "properties": {
+                "type": { "type": "string" },
+                "template": { "type": "string" },
+            },
+        },
+    })
+}
+
 impl BarPosition {
     /// Gets the orientation the bar and widgets should use
     /// based on this position.
@@ -3,7 +3,13 @@ pub mod default;
 mod r#impl;
 mod layout;
 mod marquee;
+mod pattern;
+#[cfg(feature = "config")]
+mod resolve;
 mod truncate;
+#[cfg(feature = "config")]
+pub mod validate;
+mod watch;
 
 #[cfg(feature = "battery")]
 use crate::modules::battery::BatteryModule;
@@ -48,10 +54,14 @@ use crate::modules::volume::VolumeModule;
 #[cfg(feature = "workspaces")]
 use crate::modules::workspaces::WorkspacesModule;
 
-pub use self::common::{CommonConfig, ModuleJustification, ModuleOrientation, TransitionType};
+pub use self::common::{
+    CommonConfig, EventContext, ModuleJustification, ModuleOrientation, TransitionType,
+};
 pub use self::layout::LayoutConfig;
 pub use self::marquee::{MarqueeMode, MarqueeOnHover};
+pub use self::pattern::{MonitorPattern, matches_glob};
 pub use self::truncate::{EllipsizeMode, TruncateMode};
+pub use self::watch::ConfigWatcher;
 
 use gtk::prelude::ObjectExt;
 use std::sync::OnceLock;
@@ -93,9 +103,10 @@ pub fn get_double_click_time_ms() -> u64 {
 }
 use crate::Ironbar;
 use crate::modules::{AnyModuleFactory, ModuleFactory, ModuleInfo, ModuleRef};
+use crate::script::ScriptInput;
 use crate::style::CssSource;
 use cfg_if::cfg_if;
-use color_eyre::Result;
+use color_eyre::{Report, Result};
 use config::FileFormat;
 #[cfg(feature = "extras")]
 use schemars::JsonSchema;
@@ -106,7 +117,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::{error, warn};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[cfg_attr(feature = "extras", derive(JsonSchema))]
 pub enum ModuleConfig {
@@ -219,8 +230,10 @@ impl ModuleConfig {
             ModuleConfig::Battery(_) => "Battery",
             #[cfg(feature = "bindmode")]
             ModuleConfig::Bindmode(_) => "Bindmode",
+            #[cfg(feature = "bluetooth")]
+            ModuleConfig::Bluetooth(_) => "Bluetooth",
             #[cfg(feature = "cairo")]
-            ModuleConfig::Cairo(_) => "Cario",
+            ModuleConfig::Cairo(_) => "Cairo",
             #[cfg(feature = "clipboard")]
             ModuleConfig::Clipboard(_) => "Clipboard",
             #[cfg(feature = "clock")]
@@ -263,14 +276,15 @@ impl ModuleConfig {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(JsonSchema))]
+#[cfg_attr(feature = "extras", schemars(untagged))]
 pub enum MonitorConfig {
     Single(BarConfig),
     Multiple(Vec<BarConfig>),
 }
 
-#[derive(Debug, Default, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 #[cfg_attr(feature = "extras", derive(JsonSchema))]
 pub enum BarPosition {
@@ -297,7 +311,7 @@ pub struct MarginConfig {
 /// or within an object in the [monitors](#monitors) config,
 /// depending on your [use-case](#2-pick-your-use-case).
 ///
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(JsonSchema))]
 #[serde(default)]
 pub struct BarConfig {
@@ -399,6 +413,29 @@ pub struct BarConfig {
     /// **Default**: `null`
     pub autohide: Option<u64>,
 
+    /// Whether to base this bar on the top-level bar config.
+    /// Only valid for bars inside [monitors](#monitors).
+    ///
+    /// Options set on this bar are deep-merged on top of the top-level options,
+    /// so only the differences need to be written.
+    /// The top-level `name` is not inherited, as each bar needs a unique name.
+    ///
+    /// **Default**: `false`
+    pub extend: bool,
+
+    /// How the `start`, `center` and `end` module lists of an extending bar
+    /// are combined with those of the top-level bar.
+    ///
+    /// - `replace` uses this bar's list in place of the top-level one.
+    /// - `append` adds this bar's modules after the top-level ones.
+    ///
+    /// Lists not set on this bar always use the top-level list.
+    ///
+    /// **Valid options**: `replace`, `append`
+    /// <br>
+    /// **Default**: `replace`
+    pub merge_modules: MergeModules,
+
     /// An array of modules to append to the start of the bar.
     /// Depending on the orientation, this is either the top of the left edge.
     ///
@@ -434,11 +471,22 @@ impl Default for BarConfig {
             anchor_to_edges: true,
             popup_gap: 5,
             popup_autohide: false,
+            extend: false,
+            merge_modules: MergeModules::default(),
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "extras", derive(JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MergeModules {
+    #[default]
+    Replace,
+    Append,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, PartialEq)]
 #[cfg_attr(feature = "extras", derive(JsonSchema))]
 #[serde(default)]
 pub struct Config {
@@ -464,24 +512,77 @@ pub struct Config {
     /// ```
     pub ironvar_defaults: Option<HashMap<Box<str>, String>>,
 
+    /// A list of [ironvar](ironvars) keys whose values are saved
+    /// each time they change, and restored when Ironbar starts.
+    /// A trailing `*` matches any key starting with the preceding text.
+    ///
+    /// Values are stored in `$XDG_STATE_HOME/ironbar`,
+    /// and take priority over `ironvar_defaults`.
+    ///
+    /// **Default**: `[]`
+    ///
+    /// # Example
+    ///
+    /// ```corn
+    /// { persistent_ironvars = [ "focus_mode" "audio_*" ] }
+    /// ```
+    pub persistent_ironvars: Vec<Box<str>>,
+
+    /// A list of read-only [ironvar namespaces](ironvars#namespaces)
+    /// to start on launch, so their values can be referenced
+    /// without a module which uses them.
+    ///
+    /// **Default**: `[]`
+    ///
+    /// # Example
+    ///
+    /// ```corn
+    /// { ironvar_namespaces = [ "music" "volume" ] }
+    /// ```
+    pub ironvar_namespaces: Vec<Box<str>>,
+
+    /// A map of [ironvar](ironvars) keys to scripts,
+    /// which run each time the value of a matching variable changes.
+    /// Keys may contain `*` and `?` wildcards.
+    ///
+    /// The script receives the variable key, old value and new value
+    /// in the `IRONVAR_KEY`, `IRONVAR_OLD_VALUE` and `IRONVAR_NEW_VALUE` environment variables.
+    ///
+    /// **Default**: `{}`
+    ///
+    /// # Example
+    ///
+    /// ```corn
+    /// { on_var_change.focus_mode = "notify-send \"Focus mode: $IRONVAR_NEW_VALUE\"" }
+    /// ```
+    pub on_var_change: HashMap<Box<str>, ScriptInput>,
+
     /// The configuration for the bar.
     /// Setting through this will enable a single identical bar on each monitor.
     #[serde(flatten)]
     pub bar: BarConfig,
 
     /// A map of monitor names to configs.
-    /// Monitor names can be supplied in two formats:
+    /// Monitor names can be supplied in several formats:
     ///
     /// - Connector names (`DP-1`, `HDMI-2`)
     /// - Descriptions (`ASUSTek COMPUTER INC PA278QV M4LMQS060475`).
     ///   A `starts_with` is applied allowing you to omit part of the description if convenient.
+    /// - Globs (`HDMI-*`, `*PA278QV*`), where `*` matches any number of characters
+    ///   and `?` matches exactly one. The whole connector name or description must match.
+    /// - Regular expressions wrapped in slashes (`/^DP-[12]$/`),
+    ///   which may match anywhere in the connector name or description.
+    ///
+    /// Matching is case-insensitive. An exact connector name always takes priority,
+    /// followed by description prefixes, then globs, then regular expressions.
     ///
     /// The config values can be either:
     ///
     /// - a single object, which denotes a single bar for that monitor,
     /// - an array of multiple objects, which denotes multiple for that monitor.
     ///
-    /// Providing this option overrides the single, global `bar` option.
+    /// Providing this option overrides the single, global `bar` option,
+    /// unless a bar sets `extend` to build on top of it.
     pub monitors: Option<HashMap<String, MonitorConfig>>,
 
     /// The name of the GTK icon theme to use.
@@ -502,10 +603,46 @@ pub struct Config {
     /// **Default**: `250`
     #[serde(default)]
     pub double_click_time: DoubleClickTime,
+
+    /// Whether to watch the config file for changes,
+    /// and automatically reload when it is modified.
+    ///
+    /// The new config is loaded before any bars are closed.
+    /// If it cannot be loaded, the errors are logged and the existing bars are kept.
+    ///
+    /// **Default**: `false`
+    pub watch_config: bool,
+
+    /// A list of other config files to load and merge into this one.
+    /// Paths are relative to the including file, and may start with `~/`.
+    ///
+    /// Files are merged in order, with this file taking priority.
+    /// Maps are merged key by key, while any other value (including arrays) is replaced.
+    ///
+    /// Includes are resolved when the config is loaded.
+    ///
+    /// **Default**: `[]`
+    #[serde(skip_deserializing)]
+    pub include: Vec<PathBuf>,
+
+    /// A map of template names to partial module configs.
+    /// Modules can set `template` to base themselves on a template,
+    /// overriding any of its options.
+    ///
+    /// See [templates](configuration-guide#templates) for more info.
+    ///
+    /// **Default**: `{}`
+    #[serde(skip_deserializing)]
+    #[cfg_attr(feature = "extras", schemars(schema_with = "r#impl::schema_templates"))]
+    pub templates: HashMap<String, ModuleConfig>,
+
+    /// The files the config was loaded from.
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
 }
 
 /// Double-click time configuration
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum DoubleClickTime {
@@ -522,7 +659,23 @@ impl Default for DoubleClickTime {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+cfg_if! {
+    if #[cfg(feature = "config+corn")] {
+        const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.corn"), FileFormat::Corn);
+        const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.corn"), FileFormat::Corn);
+    } else if #[cfg(feature = "config+json")] {
+        const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.json"), FileFormat::Json);
+        const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.json"), FileFormat::Json);
+    } else if #[cfg(feature = "config+yaml")] {
+        const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.yaml"), FileFormat::Yaml);
+        const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.yaml"), FileFormat::Yaml);
+    } else if #[cfg(feature = "config+toml")] {
+        const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.toml"), FileFormat::Toml);
+        const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.toml"), FileFormat::Toml);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum ConfigLocation {
     Minimal,
@@ -568,30 +721,12 @@ impl Config {
         config_location: ConfigLocation,
         css_location: Option<ConfigLocation>,
     ) -> (Config, CssSource) {
-        cfg_if! {
-            if #[cfg(feature = "config+corn")] {
-                const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.corn"), FileFormat::Corn);
-                const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.corn"), FileFormat::Corn);
-            } else if #[cfg(feature = "config+json")] {
-                const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.json"), FileFormat::Json);
-                const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.json"), FileFormat::Json);
-            } else if #[cfg(feature = "config+yaml")] {
-                const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.yaml"), FileFormat::Yaml);
-                const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.yaml"), FileFormat::Yaml);
-            } else if #[cfg(feature = "config+toml")] {
-                const CONFIG_MINIMAL: (&str, FileFormat) = (include_str!("../../examples/minimal/config.toml"), FileFormat::Toml);
-                const CONFIG_DESKTOP: (&str, FileFormat) = (include_str!("../../examples/desktop/config.toml"), FileFormat::Toml);
-            }
-        }
-
         const CSS_MINIMAL: CssSource =
             CssSource::String(include_str!("../../examples/minimal/style.css"));
 
         const CSS_DESKTOP: CssSource =
             CssSource::String(include_str!("../../examples/desktop/style.css"));
 
-        let config_builder = config::Config::builder();
-
         let css_source = match css_location.unwrap_or_else(|| config_location.clone()) {
             ConfigLocation::Minimal => CSS_MINIMAL,
             ConfigLocation::Desktop => CSS_DESKTOP,
@@ -614,45 +749,128 @@ impl Config {
             }
         };
 
-        let config_builder = match config_location {
-            ConfigLocation::Minimal => config_builder
-                .add_source(config::File::from_str(CONFIG_MINIMAL.0, CONFIG_MINIMAL.1)),
-            ConfigLocation::Desktop => config_builder
-                .add_source(config::File::from_str(CONFIG_DESKTOP.0, CONFIG_DESKTOP.1)),
-            ConfigLocation::Custom(path) => config_builder.add_source(config::File::from(path)),
-        };
+        let config = Self::try_load(&config_location).unwrap_or_else(|err| {
+            error!("Error loading config: {err:?}");
+            config::Config::builder()
+                .add_source(config::File::from_str(CONFIG_MINIMAL.0, CONFIG_MINIMAL.1))
+                .build()
+                .expect("should be a valid config")
+                .try_deserialize::<Config>()
+                .expect("should be a valid config")
+                .apply_globals()
+        });
+
+        (config, css_source)
+    }
+
+    /// Loads and validates the config at `config_location`.
+    ///
+    /// Unlike [`Config::load`], this does not fall back to the minimal config on error,
+    /// allowing the caller to keep its existing config instead.
+    ///
+    /// Only a config which cannot be read or deserialized
+    /// (including any `IRONBAR_*` environment overrides) causes an error.
+    /// Anything else found by the validator is logged as a warning.
+    #[cfg(feature = "config")]
+    pub fn try_load(config_location: &ConfigLocation) -> Result<Config> {
+        let (config, report) = Self::read(config_location)?;
+
+        for diagnostic in &report.diagnostics {
+            warn!("{diagnostic}");
+        }
 
-        let mut config: Config = config_builder
+        Ok(config.apply_globals())
+    }
+
+    /// Checks that the config at `config_location` would load with [`Config::try_load`],
+    /// without applying it.
+    #[cfg(feature = "config")]
+    pub fn check(config_location: &ConfigLocation) -> Result<()> {
+        Self::read(config_location).map(|_| ())
+    }
+
+    /// Resolves, validates and deserializes the config at `config_location`.
+    #[cfg(feature = "config")]
+    fn read(config_location: &ConfigLocation) -> Result<(Config, validate::Report)> {
+        let resolved = resolve::resolve(config_location);
+        let report = validate::validate_resolved(&resolved);
+
+        // the tree is incomplete if any files failed to load
+        if !resolved.problems.is_empty() {
+            return Err(Report::msg(report.to_string()));
+        }
+
+        let sources = resolved.paths();
+
+        let config = config::Config::builder()
+            .add_source(resolve::ResolvedSource(resolved.root))
             .add_source(config::Environment::with_prefix("IRONBAR_"))
             .build()
-            .and_then(|conf| conf.try_deserialize())
-            .unwrap_or_else(|err| {
-                error!("Error loading config: {err:?}");
-                config::Config::builder()
-                    .add_source(config::File::from_str(CONFIG_MINIMAL.0, CONFIG_MINIMAL.1))
-                    .build()
-                    .expect("should be a valid config")
-                    .try_deserialize()
-                    .expect("should be a valid config")
-            });
+            .and_then(|conf| conf.try_deserialize::<Config>());
+
+        match config {
+            Ok(mut config) => {
+                config.sources = sources;
+                Ok((config, report))
+            }
+            // the report points to where the problem is, if it found it
+            Err(err) if report.has_errors() => Err(Report::new(err).wrap_err(report.to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
 
+    /// Applies the parts of the config which are stored globally,
+    /// rather than read from the config by each bar.
+    #[cfg(feature = "config")]
+    fn apply_globals(mut self) -> Self {
         #[cfg(feature = "ipc")]
-        if let Some(ironvars) = config.ironvar_defaults.take() {
-            use crate::ironvar::WritableNamespace;
+        {
+            use crate::ironvar::{Namespace, WritableNamespace};
+            use crate::script::Script;
 
             let variable_manager = Ironbar::variable_manager();
-            for (k, v) in ironvars {
-                if variable_manager.set(&k, v).is_err() {
-                    warn!("Ignoring invalid ironvar: '{k}'");
+            variable_manager.set_persistent(self.persistent_ironvars.clone());
+
+            if let Some(ironvars) = self.ironvar_defaults.take() {
+                for (k, v) in ironvars {
+                    // restored values take priority over defaults
+                    if variable_manager.is_persistent(&k) && variable_manager.get(&k).is_some() {
+                        continue;
+                    }
+
+                    if variable_manager.set(&k, v).is_err() {
+                        warn!("Ignoring invalid ironvar: '{k}'");
+                    }
                 }
             }
+
+            let hooks = self
+                .on_var_change
+                .iter()
+                .map(|(pattern, script)| (pattern.clone(), Script::from(script.clone())))
+                .collect();
+            variable_manager.set_change_hooks(hooks);
         }
 
         // Store the double-click time globally
         // GTK's setting will be set lazily on first use (after GTK is initialized)
-        set_double_click_time(config.double_click_time.clone());
+        set_double_click_time(self.double_click_time.clone());
 
-        (config, css_source)
+        self
+    }
+
+    #[cfg(not(feature = "config"))]
+    pub fn try_load(_config_location: &ConfigLocation) -> Result<Config> {
+        Err(Report::msg(
+            "Ironbar has been configured without config support",
+        ))
+    }
+
+    #[cfg(not(feature = "config"))]
+    pub fn check(_config_location: &ConfigLocation) -> Result<()> {
+        Err(Report::msg(
+            "Ironbar has been configured without config support",
+        ))
     }
 
     #[cfg(not(feature = "config"))]
@@ -0,0 +1,212 @@
+use super::{Config, MonitorConfig};
+use regex::{Regex, RegexBuilder};
+use tracing::warn;
+
+/// Checks whether `key` matches a glob `pattern`.
+///
+/// `*` matches any number of characters (including none),
+/// and `?` matches exactly one character.
+/// Any other character must match exactly.
+pub fn matches_glob(pattern: &str, key: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let key = key.chars().collect::<Vec<_>>();
+
+    let (mut p, mut k) = (0, 0);
+    // position of the last `*` in the pattern,
+    // and the key position it was matched against
+    let mut backtrack = None;
+
+    while k < key.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, k));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == key[k] => {
+                p += 1;
+                k += 1;
+            }
+            _ => match backtrack {
+                // let the last `*` consume one more character and retry
+                Some((star, star_k)) => {
+                    backtrack = Some((star, star_k + 1));
+                    p = star + 1;
+                    k = star_k + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A key in the `monitors` map,
+/// matched against a monitor's connector name and description.
+#[derive(Debug, Clone)]
+pub enum MonitorPattern {
+    /// Matches the exact connector name,
+    /// or the start of the description.
+    Prefix(String),
+    /// Matches the whole connector name or description,
+    /// with `*` and `?` wildcards.
+    Glob(String),
+    /// Matches anywhere in the connector name or description.
+    Regex(Regex),
+}
+
+impl MonitorPattern {
+    /// Parses a `monitors` key.
+    ///
+    /// Keys wrapped in slashes (`/^DP-\d$/`) are regular expressions,
+    /// and keys containing `*` or `?` are globs.
+    /// Anything else is a connector name or description prefix.
+    pub fn parse(key: &str) -> Result<Self, regex::Error> {
+        if let Some(regex) = key
+            .strip_prefix('/')
+            .and_then(|key| key.strip_suffix('/'))
+            .filter(|regex| !regex.is_empty())
+        {
+            RegexBuilder::new(regex)
+                .case_insensitive(true)
+                .build()
+                .map(Self::Regex)
+        } else if key.contains(['*', '?']) {
+            Ok(Self::Glob(key.to_lowercase()))
+        } else {
+            Ok(Self::Prefix(key.to_lowercase()))
+        }
+    }
+
+    /// Checks whether a monitor matches the pattern.
+    /// All patterns are case-insensitive.
+    pub fn matches(&self, name: &str, description: &str) -> bool {
+        let name = name.to_lowercase();
+        let description = description.to_lowercase();
+
+        match self {
+            Self::Prefix(prefix) => name == *prefix || description.starts_with(prefix),
+            Self::Glob(glob) => matches_glob(glob, &name) || matches_glob(glob, &description),
+            Self::Regex(regex) => regex.is_match(&name) || regex.is_match(&description),
+        }
+    }
+
+    /// Lower values are tried first,
+    /// so that more specific patterns win.
+    fn priority(&self) -> u8 {
+        match self {
+            Self::Prefix(_) => 0,
+            Self::Glob(_) => 1,
+            Self::Regex(_) => 2,
+        }
+    }
+}
+
+impl Config {
+    /// Gets the config for the monitor with the given connector name and description.
+    ///
+    /// An exact connector name match always wins.
+    /// Otherwise, description prefixes are tried first, then globs, then regular expressions.
+    /// Longer keys of the same kind are tried first, as they are usually more specific.
+    pub fn monitor_config(&self, name: &str, description: &str) -> Option<&MonitorConfig> {
+        let monitors = self.monitors.as_ref()?;
+
+        if let Some(config) = monitors.get(name) {
+            return Some(config);
+        }
+
+        let mut patterns = monitors
+            .keys()
+            .filter_map(|key| match MonitorPattern::parse(key) {
+                Ok(pattern) => Some((pattern, key)),
+                Err(err) => {
+                    warn!("Ignoring invalid monitor pattern '{key}': {err}");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        patterns.sort_by(|(a, a_key), (b, b_key)| {
+            a.priority()
+                .cmp(&b.priority())
+                .then_with(|| b_key.len().cmp(&a_key.len()))
+                .then_with(|| a_key.cmp(b_key))
+        });
+
+        patterns
+            .into_iter()
+            .find(|(pattern, _)| pattern.matches(name, description))
+            .and_then(|(_, key)| monitors.get(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BarConfig;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_matches_glob() {
+        assert!(matches_glob("focus_mode", "focus_mode"));
+        assert!(!matches_glob("focus_mode", "focus_mode_2"));
+        assert!(matches_glob("audio_*", "audio_profile"));
+        assert!(matches_glob("*", "anything"));
+        assert!(!matches_glob("audio_*", "focus_mode"));
+        assert!(matches_glob("audio_*_level", "audio_sink_level"));
+        assert!(matches_glob("*_mode", "focus_mode"));
+        assert!(matches_glob("a*b*c", "axxbyyc"));
+        assert!(!matches_glob("a*b*c", "axxbyy"));
+        assert!(matches_glob("v?r", "var"));
+        assert!(!matches_glob("v?r", "vr"));
+    }
+
+    #[test]
+    fn test_monitor_pattern() {
+        let desc = "ASUSTek COMPUTER INC PA278QV M4LMQS060475";
+
+        let parse = |key| MonitorPattern::parse(key).expect("to be valid");
+
+        assert!(parse("DP-1").matches("DP-1", desc));
+        assert!(!parse("DP").matches("DP-1", ""));
+        assert!(parse("asustek").matches("DP-1", desc));
+        assert!(parse("HDMI-*").matches("HDMI-A-1", ""));
+        assert!(!parse("HDMI-*").matches("DP-1", desc));
+        assert!(parse("*PA278QV*").matches("DP-1", desc));
+        assert!(parse(r"/^dp-\d$/").matches("DP-2", ""));
+        assert!(!parse(r"/^dp-\d$/").matches("DP-10", ""));
+        assert!(parse("/M4LMQS/").matches("DP-1", desc));
+
+        assert!(MonitorPattern::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn test_monitor_config_priority() {
+        let bar = |name: &str| {
+            MonitorConfig::Single(BarConfig {
+                name: Some(name.to_string()),
+                ..BarConfig::default()
+            })
+        };
+
+        let monitors = ["DP-1", "DP-*", "/^dp/", "Dell", "*"]
+            .into_iter()
+            .map(|key| (key.to_string(), bar(key)))
+            .collect::<HashMap<_, _>>();
+
+        let config = Config {
+            monitors: Some(monitors),
+            ..Config::default()
+        };
+
+        let name = |monitor, desc| match config.monitor_config(monitor, desc) {
+            Some(MonitorConfig::Single(bar)) => bar.name.clone(),
+            _ => None,
+        };
+
+        assert_eq!(name("DP-1", "Dell U2720Q").as_deref(), Some("DP-1"));
+        assert_eq!(name("DP-2", "Dell U2720Q").as_deref(), Some("Dell"));
+        assert_eq!(name("DP-2", "LG").as_deref(), Some("DP-*"));
+        assert_eq!(name("HDMI-A-1", "LG").as_deref(), Some("*"));
+    }
+}
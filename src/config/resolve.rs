@@ -0,0 +1,809 @@
+//! Loading of raw config files.
+//!
+//! Files listed under `include` are loaded and deep-merged,
+//! modules referencing one of the `templates` are expanded,
+//! and monitor bars which `extend` the top-level bar are merged onto it,
+//! before the result is deserialized.
+
+use super::validate::{DiagnosticKind, MODULE_LOCATIONS, Segment, find_file};
+use super::{CONFIG_DESKTOP, CONFIG_MINIMAL, ConfigLocation};
+use config::{ConfigError, FileFormat, Map, Value, ValueKind};
+use std::path::{Path, PathBuf};
+
+/// Keys of the top-level config which make up the bar,
+/// and are inherited by monitor bars which `extend` it.
+///
+/// `name` is left out, as bar names must be unique.
+const BAR_KEYS: [&str; 13] = [
+    "position",
+    "anchor_to_edges",
+    "height",
+    "margin",
+    "layer",
+    "exclusive_zone",
+    "popup_gap",
+    "popup_autohide",
+    "start_hidden",
+    "autohide",
+    "start",
+    "center",
+    "end",
+];
+
+/// Keys which control how a monitor bar extends the top-level bar.
+const EXTEND_KEYS: [&str; 2] = ["extend", "merge_modules"];
+
+/// A config file which was read while resolving.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// Display name, used as the origin of values from this file.
+    pub name: String,
+    /// Path on disk. Not set for built-in configs.
+    pub path: Option<PathBuf>,
+    pub text: String,
+}
+
+/// A problem encountered while resolving.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub kind: DiagnosticKind,
+    /// The file to blame.
+    /// If not set, this is determined from the value at `path`.
+    pub file: Option<String>,
+    pub path: Vec<Segment>,
+    pub message: String,
+}
+
+/// The fully resolved config.
+#[derive(Debug)]
+pub struct Resolved {
+    /// The merged config tree.
+    /// Only valid if there are no problems.
+    pub root: Value,
+    pub files: Vec<SourceFile>,
+    pub problems: Vec<Problem>,
+}
+
+impl Resolved {
+    /// Gets the paths of every file read.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files
+            .iter()
+            .filter_map(|file| file.path.clone())
+            .collect()
+    }
+}
+
+/// Source for the resolved config tree,
+/// allowing other sources such as environment variables to be layered on top.
+#[derive(Debug, Clone)]
+pub struct ResolvedSource(pub Value);
+
+impl config::Source for ResolvedSource {
+    fn clone_into_box(&self) -> Box<dyn config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        match &self.0.kind {
+            ValueKind::Table(table) => Ok(table.clone()),
+            _ => Ok(Map::new()),
+        }
+    }
+}
+
+/// Reads the config at `location`, including any files it includes,
+/// and expands module templates.
+pub fn resolve(location: &ConfigLocation) -> Resolved {
+    let mut resolver = Resolver::default();
+
+    let root = match location {
+        ConfigLocation::Minimal => {
+            resolver.load_str("minimal", None, CONFIG_MINIMAL.0, CONFIG_MINIMAL.1)
+        }
+        ConfigLocation::Desktop => {
+            resolver.load_str("desktop", None, CONFIG_DESKTOP.0, CONFIG_DESKTOP.1)
+        }
+        ConfigLocation::Custom(path) => match resolver.load_file(path) {
+            Ok(root) => root,
+            Err(message) => {
+                resolver.problem(
+                    Some(path.display().to_string()),
+                    DiagnosticKind::Syntax,
+                    vec![],
+                    message,
+                );
+                None
+            }
+        },
+    };
+
+    resolver.finish(root)
+}
+
+/// Resolves config `text` in the given format.
+/// `name` is used only for reporting.
+#[cfg(test)]
+pub fn resolve_str(name: &str, text: &str, format: FileFormat) -> Resolved {
+    let mut resolver = Resolver::default();
+    let root = resolver.load_str(name, None, text, format);
+    resolver.finish(root)
+}
+
+#[derive(Debug, Default)]
+struct Resolver {
+    files: Vec<SourceFile>,
+    problems: Vec<Problem>,
+    /// Canonical paths of the files currently being loaded,
+    /// used to detect include cycles.
+    stack: Vec<PathBuf>,
+}
+
+impl Resolver {
+    fn finish(mut self, root: Option<Value>) -> Resolved {
+        let mut root = root.unwrap_or_else(|| Value::new(None, ValueKind::Table(Map::new())));
+
+        if self.problems.is_empty() {
+            self.expand_templates(&mut root);
+            self.extend_monitor_bars(&mut root);
+        }
+
+        Resolved {
+            root,
+            files: self.files,
+            problems: self.problems,
+        }
+    }
+
+    fn problem(
+        &mut self,
+        file: Option<String>,
+        kind: DiagnosticKind,
+        path: Vec<Segment>,
+        message: impl Into<String>,
+    ) {
+        self.problems.push(Problem {
+            kind,
+            file,
+            path,
+            message: message.into(),
+        });
+    }
+
+    /// Loads the file at `path`, trying each supported extension.
+    ///
+    /// Returns an error if the file cannot be read.
+    /// Problems inside the file are recorded instead.
+    fn load_file(&mut self, path: &Path) -> Result<Option<Value>, String> {
+        let (path, format) =
+            find_file(path).ok_or("no config file with a supported format was found")?;
+
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+        if self.stack.contains(&canonical) {
+            return Err(format!("'{}' includes itself", path.display()));
+        }
+
+        let text = std::fs::read_to_string(&path).map_err(|err| err.to_string())?;
+
+        self.stack.push(canonical);
+        let value = self.load_str(&path.display().to_string(), Some(path), &text, format);
+        self.stack.pop();
+
+        Ok(value)
+    }
+
+    /// Parses config `text`, and merges in any files it includes.
+    fn load_str(
+        &mut self,
+        name: &str,
+        path: Option<PathBuf>,
+        text: &str,
+        format: FileFormat,
+    ) -> Option<Value> {
+        let base_dir = path
+            .as_deref()
+            .and_then(Path::parent)
+            .map(Path::to_path_buf);
+
+        self.files.push(SourceFile {
+            name: name.to_string(),
+            path,
+            text: text.to_string(),
+        });
+
+        let mut value = match config::Config::builder()
+            .add_source(config::File::from_str(text, format))
+            .build()
+        {
+            Ok(config) => with_origin(config.cache, name),
+            Err(err) => {
+                let message = match err {
+                    ConfigError::FileParse { cause, .. } => cause.to_string(),
+                    err => err.to_string(),
+                };
+
+                self.problem(
+                    Some(name.to_string()),
+                    DiagnosticKind::Syntax,
+                    vec![],
+                    message,
+                );
+                return None;
+            }
+        };
+
+        let includes = match &mut value.kind {
+            ValueKind::Table(table) => table.remove("include"),
+            _ => None,
+        };
+
+        let Some(includes) = includes else {
+            return Some(value);
+        };
+
+        let include_path = vec![Segment::Key("include".to_string())];
+        let ValueKind::Array(includes) = includes.kind else {
+            self.problem(
+                Some(name.to_string()),
+                DiagnosticKind::Invalid,
+                include_path,
+                "expected an array of file paths",
+            );
+            return Some(value);
+        };
+
+        let mut merged = Value::new(None, ValueKind::Table(Map::new()));
+
+        for (i, include) in includes.into_iter().enumerate() {
+            let mut path = include_path.clone();
+            path.push(Segment::Index(i));
+
+            let ValueKind::String(include) = include.kind else {
+                self.problem(
+                    Some(name.to_string()),
+                    DiagnosticKind::Invalid,
+                    path,
+                    "expected a file path",
+                );
+                continue;
+            };
+
+            let include = expand_path(&include, base_dir.as_deref());
+            match self.load_file(&include) {
+                Ok(Some(value)) => merge(&mut merged, value),
+                Ok(None) => {}
+                Err(message) => self.problem(
+                    Some(name.to_string()),
+                    DiagnosticKind::Invalid,
+                    path,
+                    format!("failed to include '{}': {message}", include.display()),
+                ),
+            }
+        }
+
+        // the including file takes priority over anything it includes
+        merge(&mut merged, value);
+        Some(merged)
+    }
+
+    /// Replaces each module referencing a template
+    /// with the template merged with the module's own options.
+    fn expand_templates(&mut self, root: &mut Value) {
+        let ValueKind::Table(table) = &mut root.kind else {
+            return;
+        };
+
+        let templates = match table.remove("templates").map(|templates| templates.kind) {
+            None => Map::new(),
+            Some(ValueKind::Table(templates)) => templates,
+            Some(_) => {
+                self.problem(
+                    None,
+                    DiagnosticKind::Invalid,
+                    vec![Segment::Key("templates".to_string())],
+                    "expected a map of template names to module configs",
+                );
+                return;
+            }
+        };
+
+        self.expand_bar_templates(root, &[], &templates);
+
+        let ValueKind::Table(table) = &mut root.kind else {
+            return;
+        };
+
+        let Some(ValueKind::Table(monitors)) =
+            table.get_mut("monitors").map(|monitors| &mut monitors.kind)
+        else {
+            return;
+        };
+
+        let mut names = monitors.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            let monitor = monitors.get_mut(&name).expect("to exist");
+            let path = vec![Segment::Key("monitors".to_string()), Segment::Key(name)];
+
+            match &mut monitor.kind {
+                ValueKind::Array(bars) => {
+                    for (i, bar) in bars.iter_mut().enumerate() {
+                        let mut path = path.clone();
+                        path.push(Segment::Index(i));
+                        self.expand_bar_templates(bar, &path, &templates);
+                    }
+                }
+                _ => self.expand_bar_templates(monitor, &path, &templates),
+            }
+        }
+    }
+
+    fn expand_bar_templates(
+        &mut self,
+        bar: &mut Value,
+        path: &[Segment],
+        templates: &Map<String, Value>,
+    ) {
+        let ValueKind::Table(bar) = &mut bar.kind else {
+            return;
+        };
+
+        for location in MODULE_LOCATIONS {
+            let Some(ValueKind::Array(modules)) =
+                bar.get_mut(location).map(|value| &mut value.kind)
+            else {
+                continue;
+            };
+
+            for (i, module) in modules.iter_mut().enumerate() {
+                let mut path = path.to_vec();
+                path.push(Segment::Key(location.to_string()));
+                path.push(Segment::Index(i));
+
+                self.expand_template(module, path, templates, &mut vec![]);
+            }
+        }
+    }
+
+    /// Expands `module` if it references a template,
+    /// recursing if the template itself references another template.
+    fn expand_template(
+        &mut self,
+        module: &mut Value,
+        mut path: Vec<Segment>,
+        templates: &Map<String, Value>,
+        stack: &mut Vec<String>,
+    ) {
+        let ValueKind::Table(table) = &mut module.kind else {
+            return;
+        };
+
+        let Some(template) = table.remove("template") else {
+            return;
+        };
+
+        path.push(Segment::Key("template".to_string()));
+
+        let ValueKind::String(name) = template.kind else {
+            self.problem(
+                None,
+                DiagnosticKind::Invalid,
+                path,
+                "expected a template name",
+            );
+            return;
+        };
+
+        if stack.contains(&name) {
+            self.problem(
+                None,
+                DiagnosticKind::Invalid,
+                path,
+                format!("template `{name}` references itself"),
+            );
+            return;
+        }
+
+        let Some(mut base) = templates.get(&name).cloned() else {
+            self.problem(
+                None,
+                DiagnosticKind::Invalid,
+                path,
+                format!("unknown template `{name}`"),
+            );
+            return;
+        };
+
+        stack.push(name.clone());
+        self.expand_template(
+            &mut base,
+            vec![Segment::Key("templates".to_string()), Segment::Key(name)],
+            templates,
+            stack,
+        );
+        stack.pop();
+
+        let origin = module.origin().map(ToString::to_string);
+        merge(&mut base, module.clone());
+        *module = Value::new(origin.as_ref(), base.kind);
+    }
+
+    /// Merges each monitor bar which sets `extend`
+    /// onto the top-level bar config.
+    fn extend_monitor_bars(&mut self, root: &mut Value) {
+        let ValueKind::Table(table) = &mut root.kind else {
+            return;
+        };
+
+        for key in EXTEND_KEYS {
+            if table.contains_key(key) {
+                self.problem(
+                    None,
+                    DiagnosticKind::Invalid,
+                    vec![Segment::Key(key.to_string())],
+                    "only valid for bars inside `monitors`",
+                );
+            }
+        }
+
+        let base = table
+            .iter()
+            .filter(|(key, _)| BAR_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Map<_, _>>();
+
+        let Some(ValueKind::Table(monitors)) =
+            table.get_mut("monitors").map(|monitors| &mut monitors.kind)
+        else {
+            return;
+        };
+
+        let mut names = monitors.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            let monitor = monitors.get_mut(&name).expect("to exist");
+            let path = vec![Segment::Key("monitors".to_string()), Segment::Key(name)];
+
+            match &mut monitor.kind {
+                ValueKind::Array(bars) => {
+                    for (i, bar) in bars.iter_mut().enumerate() {
+                        let mut path = path.clone();
+                        path.push(Segment::Index(i));
+                        self.extend_bar(bar, &path, &base);
+                    }
+                }
+                _ => self.extend_bar(monitor, &path, &base),
+            }
+        }
+    }
+
+    /// Replaces `bar` with `base` merged with the bar's own options,
+    /// if the bar sets `extend`.
+    fn extend_bar(&mut self, bar: &mut Value, path: &[Segment], base: &Map<String, Value>) {
+        let ValueKind::Table(table) = &mut bar.kind else {
+            return;
+        };
+
+        let extend = table.remove("extend");
+        let merge_modules = table.remove("merge_modules");
+
+        let mut problem = |key: &str, value: &Value, message: &str| {
+            let mut path = path.to_vec();
+            path.push(Segment::Key(key.to_string()));
+            let file = value.origin().map(ToString::to_string);
+            self.problem(file, DiagnosticKind::Invalid, path, message);
+        };
+
+        let extend = match extend {
+            None => false,
+            Some(value) => match value.kind {
+                ValueKind::Boolean(extend) => extend,
+                _ => {
+                    problem("extend", &value, "expected a boolean");
+                    return;
+                }
+            },
+        };
+
+        let append = match merge_modules {
+            None => false,
+            Some(value) => match &value.kind {
+                ValueKind::String(mode) if mode == "replace" => false,
+                ValueKind::String(mode) if mode == "append" => true,
+                _ => {
+                    problem("merge_modules", &value, "expected `replace` or `append`");
+                    return;
+                }
+            },
+        };
+
+        if !extend {
+            return;
+        }
+
+        if append {
+            for location in MODULE_LOCATIONS {
+                let Some(ValueKind::Array(modules)) =
+                    table.get_mut(location).map(|value| &mut value.kind)
+                else {
+                    continue;
+                };
+
+                if let Some(ValueKind::Array(base_modules)) =
+                    base.get(location).map(|value| &value.kind)
+                {
+                    modules.splice(0..0, base_modules.iter().cloned());
+                }
+            }
+        }
+
+        let mut merged = Value::new(None, ValueKind::Table(base.clone()));
+        let origin = bar.origin().map(ToString::to_string);
+        merge(&mut merged, bar.clone());
+        *bar = Value::new(origin.as_ref(), merged.kind);
+    }
+}
+
+/// Deep-merges `overlay` into `base`.
+///
+/// Tables are merged key by key,
+/// while any other value in `overlay` (including arrays) replaces that in `base`.
+pub fn merge(base: &mut Value, overlay: Value) {
+    let origin = overlay.origin().map(ToString::to_string);
+
+    match (&mut base.kind, overlay.kind) {
+        (ValueKind::Table(base), ValueKind::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (_, kind) => *base = Value::new(origin.as_ref(), kind),
+    }
+}
+
+/// Recursively sets the origin of `value` and its children,
+/// so that problems can be traced back to their file.
+fn with_origin(value: Value, origin: &str) -> Value {
+    let origin = origin.to_string();
+
+    let kind = match value.kind {
+        ValueKind::Table(table) => ValueKind::Table(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, with_origin(value, &origin)))
+                .collect(),
+        ),
+        ValueKind::Array(array) => ValueKind::Array(
+            array
+                .into_iter()
+                .map(|value| with_origin(value, &origin))
+                .collect(),
+        ),
+        kind => kind,
+    };
+
+    Value::new(Some(&origin), kind)
+}
+
+/// Resolves an included path,
+/// relative to the directory of the file including it.
+fn expand_path(path: &str, base_dir: Option<&Path>) -> PathBuf {
+    let path = match path.strip_prefix("~/") {
+        Some(path) => dirs::home_dir().unwrap_or_default().join(path),
+        None => PathBuf::from(path),
+    };
+
+    match base_dir {
+        Some(base_dir) if path.is_relative() => base_dir.join(path),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get<'a>(value: &'a Value, path: &[Segment]) -> &'a ValueKind {
+        path.iter()
+            .fold(&value.kind, |kind, segment| match (segment, kind) {
+                (Segment::Key(key), ValueKind::Table(table)) => &table[key].kind,
+                (Segment::Index(index), ValueKind::Array(array)) => &array[*index].kind,
+                _ => panic!("no value at {segment:?}"),
+            })
+    }
+
+    fn key(key: &str) -> Segment {
+        Segment::Key(key.to_string())
+    }
+
+    #[test]
+    fn expands_template() {
+        let text = r#"{
+  "templates": { "clock_utc": { "type": "clock", "format": "%H:%M:%S", "class": "utc" } },
+  "end": [{ "template": "clock_utc", "format": "%H:%M" }]
+}"#;
+
+        let resolved = resolve_str("config.json", text, FileFormat::Json);
+        assert!(resolved.problems.is_empty());
+
+        let module = [key("end"), Segment::Index(0)];
+        let ValueKind::Table(table) = get(&resolved.root, &module) else {
+            panic!("expected table");
+        };
+
+        let mut keys = table.keys().map(String::as_str).collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!(keys, ["class", "format", "type"]);
+
+        let format = [key("end"), Segment::Index(0), key("format")];
+        assert_eq!(
+            get(&resolved.root, &format),
+            &ValueKind::String("%H:%M".to_string())
+        );
+
+        let ValueKind::Table(root) = &resolved.root.kind else {
+            panic!("expected table");
+        };
+        assert!(!root.contains_key("templates"));
+    }
+
+    #[test]
+    fn reports_template_problems() {
+        let text = r#"{
+  "templates": { "a": { "template": "b" }, "b": { "template": "a" } },
+  "start": [{ "template": "a" }, { "template": "missing" }]
+}"#;
+
+        let resolved = resolve_str("config.json", text, FileFormat::Json);
+        let messages = resolved
+            .problems
+            .iter()
+            .map(|problem| problem.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            [
+                "template `a` references itself",
+                "unknown template `missing`"
+            ]
+        );
+    }
+
+    #[test]
+    fn extends_monitor_bars() {
+        let text = r#"{
+  "name": "main",
+  "height": 30,
+  "margin": { "top": 5 },
+  "end": [{ "type": "clock" }],
+  "monitors": {
+    "DP-1": { "extend": true, "height": 40, "margin": { "left": 2 }, "start": [{ "type": "label" }] },
+    "DP-2": [{ "extend": true, "merge_modules": "append", "end": [{ "type": "label" }] }],
+    "HDMI-A-1": { "height": 10 }
+  }
+}"#;
+
+        let resolved = resolve_str("config.json", text, FileFormat::Json);
+        assert!(resolved.problems.is_empty());
+
+        let dp1 = [key("monitors"), key("DP-1")];
+        let ValueKind::Table(bar) = get(&resolved.root, &dp1) else {
+            panic!("expected table");
+        };
+
+        let mut keys = bar.keys().map(String::as_str).collect::<Vec<_>>();
+        keys.sort_unstable();
+        assert_eq!(keys, ["end", "height", "margin", "start"]);
+        assert_eq!(bar["height"].kind, ValueKind::I64(40));
+
+        let ValueKind::Table(margin) = &bar["margin"].kind else {
+            panic!("expected table");
+        };
+        assert_eq!(margin.len(), 2);
+
+        let dp2_end = [key("monitors"), key("DP-2"), Segment::Index(0), key("end")];
+        let ValueKind::Array(end) = get(&resolved.root, &dp2_end) else {
+            panic!("expected array");
+        };
+        assert_eq!(end.len(), 2);
+        assert_eq!(
+            get(&end[0], &[key("type")]),
+            &ValueKind::String("clock".to_string())
+        );
+
+        let hdmi = [key("monitors"), key("HDMI-A-1")];
+        let ValueKind::Table(bar) = get(&resolved.root, &hdmi) else {
+            panic!("expected table");
+        };
+        assert_eq!(bar.len(), 1);
+    }
+
+    #[test]
+    fn reports_extend_problems() {
+        let text = r#"{
+  "extend": true,
+  "monitors": {
+    "DP-1": { "extend": "yes" },
+    "DP-2": { "extend": true, "merge_modules": "prepend" }
+  }
+}"#;
+
+        let resolved = resolve_str("config.json", text, FileFormat::Json);
+        let messages = resolved
+            .problems
+            .iter()
+            .map(|problem| problem.message.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            messages,
+            [
+                "only valid for bars inside `monitors`",
+                "expected a boolean",
+                "expected `replace` or `append`"
+            ]
+        );
+    }
+
+    #[cfg(feature = "extras")]
+    #[test]
+    fn bar_keys_match_bar_config() {
+        let schema = schemars::schema_for!(crate::config::BarConfig);
+        let mut keys = schema.as_value()["properties"]
+            .as_object()
+            .expect("to have properties")
+            .keys()
+            .map(String::as_str)
+            .filter(|key| *key != "name" && !EXTEND_KEYS.contains(key))
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        let mut expected = BAR_KEYS.to_vec();
+        expected.sort_unstable();
+
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn merges_includes() {
+        let dir = std::env::temp_dir().join(format!("ironbar-resolve-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("to create dir");
+
+        std::fs::write(
+            dir.join("base.json"),
+            r#"{ "height": 30, "icon_overrides": { "a": "b" }, "end": [{ "type": "clock" }] }"#,
+        )
+        .expect("to write file");
+
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{ "include": ["base.json"], "icon_overrides": { "c": "d" }, "end": [] }"#,
+        )
+        .expect("to write file");
+
+        let resolved = resolve(&ConfigLocation::Custom(dir.join("config.json")));
+        std::fs::remove_dir_all(&dir).expect("to remove dir");
+
+        assert!(resolved.problems.is_empty());
+        assert_eq!(resolved.paths().len(), 2);
+
+        assert_eq!(get(&resolved.root, &[key("height")]), &ValueKind::I64(30));
+        assert_eq!(
+            get(&resolved.root, &[key("end")]),
+            &ValueKind::Array(vec![])
+        );
+
+        let ValueKind::Table(overrides) = get(&resolved.root, &[key("icon_overrides")]) else {
+            panic!("expected table");
+        };
+        assert_eq!(overrides.len(), 2);
+    }
+}
@@ -0,0 +1,848 @@
+//! Config validation, used by the `check` command.
+//!
+//! Unlike [`Config::load`], this does not stop at the first error
+//! or fall back to a default config.
+//! Each bar and module is deserialized separately so that every error is collected,
+//! and each is narrowed down to the key that caused it.
+
+use super::resolve::{Resolved, SourceFile, resolve};
+use super::{BarConfig, Config, ConfigLocation, ModuleConfig, MonitorPattern};
+use config::{ConfigError, FileFormat, Value, ValueKind};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Keys holding module arrays on each bar.
+pub(super) const MODULE_LOCATIONS: [&str; 3] = ["start", "center", "end"];
+
+/// Supported config file formats, and their extensions.
+const FORMATS: &[(FileFormat, &[&str])] = &[
+    #[cfg(feature = "config+json")]
+    (FileFormat::Json, &["json"]),
+    #[cfg(feature = "config+toml")]
+    (FileFormat::Toml, &["toml"]),
+    #[cfg(feature = "config+yaml")]
+    (FileFormat::Yaml, &["yaml", "yml"]),
+    #[cfg(feature = "config+corn")]
+    (FileFormat::Corn, &["corn"]),
+];
+
+/// The result of validating a config.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A single problem found in the config.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// The file the problem was found in.
+    pub file: String,
+    /// Best-effort 1-indexed line number.
+    pub line: Option<usize>,
+    /// Best-effort 1-indexed column number.
+    pub column: Option<usize>,
+    /// The path to the offending key, such as `monitors.DP-1[0].end[2].format`.
+    /// Empty for errors affecting the whole file.
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    /// The file could not be read or parsed.
+    Syntax,
+    /// A value could not be deserialized.
+    Invalid,
+    /// A key is not used by Ironbar.
+    UnknownKey,
+}
+
+impl Report {
+    /// Whether no problems at all were found.
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Whether any problems were found which would prevent the config loading.
+    /// Unknown keys are ignored when loading, so are not included.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.kind != DiagnosticKind::UnknownKey)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{diagnostic}")?;
+        }
+
+        let count = self.diagnostics.len();
+        write!(
+            f,
+            "found {count} problem{}",
+            if count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.file)?;
+
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{line}:{column}")?;
+        }
+
+        let kind = match self.kind {
+            DiagnosticKind::Syntax => "syntax error",
+            DiagnosticKind::Invalid => "error",
+            DiagnosticKind::UnknownKey => "unknown key",
+        };
+
+        write!(f, ": {kind}")?;
+
+        if !self.path.is_empty() {
+            write!(f, " at `{}`", self.path)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// A single step in the path to a config value.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn format_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+
+    for segment in path {
+        match segment {
+            Segment::Key(key) if out.is_empty() => out.push_str(key),
+            Segment::Key(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            Segment::Index(index) => out.push_str(&format!("[{index}]")),
+        }
+    }
+
+    out
+}
+
+/// Validates the config at the provided location,
+/// including any files it includes.
+pub fn validate(location: &ConfigLocation) -> Report {
+    validate_resolved(&resolve(location))
+}
+
+/// Finds the config file for the provided path,
+/// trying each supported extension if the path itself does not exist.
+pub(super) fn find_file(path: &Path) -> Option<(PathBuf, FileFormat)> {
+    if path.is_file() {
+        let ext = path.extension()?.to_str()?;
+        return FORMATS
+            .iter()
+            .find(|(_, extensions)| extensions.contains(&ext))
+            .map(|(format, _)| (path.to_path_buf(), *format));
+    }
+
+    FORMATS.iter().find_map(|(format, extensions)| {
+        extensions.iter().find_map(|ext| {
+            let mut file = path.as_os_str().to_os_string();
+            file.push(".");
+            file.push(ext);
+
+            let file = PathBuf::from(file);
+            file.is_file().then_some((file, *format))
+        })
+    })
+}
+
+/// Validates an already resolved config.
+pub(super) fn validate_resolved(resolved: &Resolved) -> Report {
+    let mut validator = Validator {
+        files: &resolved.files,
+        root: &resolved.root,
+        report: Report::default(),
+    };
+
+    for problem in &resolved.problems {
+        validator.push_in(
+            problem.file.as_deref(),
+            problem.kind,
+            &problem.path,
+            &problem.message,
+        );
+    }
+
+    // the tree is incomplete if any files failed to load
+    if !resolved.problems.is_empty() {
+        return validator.report;
+    }
+
+    validator.check_config();
+    #[cfg(feature = "extras")]
+    validator.check_unknown_keys();
+
+    validator.report
+}
+
+struct Validator<'a> {
+    files: &'a [SourceFile],
+    root: &'a Value,
+    report: Report,
+}
+
+impl Validator<'_> {
+    fn check_config(&mut self) {
+        let Some(table) = as_table(self.root) else {
+            self.push(DiagnosticKind::Invalid, &[], "expected an object");
+            return;
+        };
+
+        let mut root = self.root.clone();
+        if let ValueKind::Table(table) = &mut root.kind {
+            table.remove("monitors");
+            for location in MODULE_LOCATIONS {
+                table.remove(location);
+            }
+        }
+
+        self.check::<Config>(&root, &[]);
+        self.check_modules(self.root, &[]);
+
+        let Some(monitors) = table.get("monitors") else {
+            return;
+        };
+
+        let path = [Segment::Key("monitors".to_string())];
+        let Some(monitors) = as_table(monitors) else {
+            self.push(DiagnosticKind::Invalid, &path, "expected an object");
+            return;
+        };
+
+        for name in sorted_keys(monitors) {
+            let mut path = path.to_vec();
+            path.push(Segment::Key(name.clone()));
+
+            if let Err(err) = MonitorPattern::parse(name) {
+                self.push(
+                    DiagnosticKind::Invalid,
+                    &path,
+                    &format!("invalid monitor pattern: {err}"),
+                );
+            }
+
+            match &monitors[name].kind {
+                ValueKind::Table(_) => self.check_bar(&monitors[name], &path),
+                ValueKind::Array(bars) => {
+                    for (i, bar) in bars.iter().enumerate() {
+                        let mut path = path.clone();
+                        path.push(Segment::Index(i));
+                        self.check_bar(bar, &path);
+                    }
+                }
+                _ => self.push(
+                    DiagnosticKind::Invalid,
+                    &path,
+                    "expected a single bar config or array of bar configs",
+                ),
+            }
+        }
+    }
+
+    fn check_bar(&mut self, bar: &Value, path: &[Segment]) {
+        let mut stripped = bar.clone();
+        if let ValueKind::Table(table) = &mut stripped.kind {
+            for location in MODULE_LOCATIONS {
+                table.remove(location);
+            }
+        }
+
+        self.check::<BarConfig>(&stripped, path);
+        self.check_modules(bar, path);
+    }
+
+    fn check_modules(&mut self, bar: &Value, path: &[Segment]) {
+        let Some(table) = as_table(bar) else {
+            self.push(DiagnosticKind::Invalid, path, "expected an object");
+            return;
+        };
+
+        for location in MODULE_LOCATIONS {
+            let Some(modules) = table.get(location) else {
+                continue;
+            };
+
+            let mut path = path.to_vec();
+            path.push(Segment::Key(location.to_string()));
+
+            let ValueKind::Array(modules) = &modules.kind else {
+                self.push(
+                    DiagnosticKind::Invalid,
+                    &path,
+                    "expected an array of modules",
+                );
+                continue;
+            };
+
+            for (i, module) in modules.iter().enumerate() {
+                let mut path = path.clone();
+                path.push(Segment::Index(i));
+                self.check::<ModuleConfig>(module, &path);
+            }
+        }
+    }
+
+    /// Attempts to deserialize `value` as `T`,
+    /// reporting the narrowest failing path if it fails.
+    fn check<T: DeserializeOwned>(&mut self, value: &Value, path: &[Segment]) {
+        let Err(err) = T::deserialize(value.clone()) else {
+            return;
+        };
+
+        let message = err.to_string();
+
+        let mut full_path = path.to_vec();
+        let narrowed = narrow::<T>(value);
+
+        // unknown module types are only fixed by removing the whole module,
+        // so point at the type key instead.
+        if narrowed.is_empty()
+            && message.starts_with("unknown variant")
+            && as_table(value).is_some_and(|table| table.contains_key("type"))
+        {
+            full_path.push(Segment::Key("type".to_string()));
+        } else {
+            full_path.extend(narrowed);
+        }
+
+        self.push(DiagnosticKind::Invalid, &full_path, &message);
+    }
+
+    fn push(&mut self, kind: DiagnosticKind, path: &[Segment], message: &str) {
+        self.push_in(None, kind, path, message);
+    }
+
+    /// Records a diagnostic against `file`.
+    /// If not provided, the file the value at `path` was loaded from is used.
+    fn push_in(
+        &mut self,
+        file: Option<&str>,
+        kind: DiagnosticKind,
+        path: &[Segment],
+        message: &str,
+    ) {
+        let file = file.or_else(|| origin(self.root, path));
+        let source = file.map_or(self.files.first(), |file| {
+            self.files.iter().find(|source| source.name == file)
+        });
+
+        let (line, column) = source
+            .and_then(|source| {
+                locate(&source.text, self.root, path)
+                    .map(|offset| line_column(&source.text, offset))
+            })
+            .unzip();
+
+        self.report.diagnostics.push(Diagnostic {
+            kind,
+            file: file
+                .or(source.map(|source| source.name.as_str()))
+                .unwrap_or_default()
+                .to_string(),
+            line,
+            column,
+            path: format_path(path),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Gets the origin of the deepest value along `path`.
+fn origin<'a>(root: &'a Value, path: &[Segment]) -> Option<&'a str> {
+    let mut origin = root.origin();
+    let mut value = root;
+
+    for segment in path {
+        let Some(child) = get(value, std::slice::from_ref(segment)) else {
+            break;
+        };
+
+        value = child;
+        origin = child.origin().or(origin);
+    }
+
+    origin
+}
+
+/// Finds the deepest path inside `value` which causes deserialization to fail.
+///
+/// At each level, each child is removed in turn.
+/// If removing a child allows deserialization to succeed,
+/// or fails only because that child is required, it is the culprit,
+/// and the search continues inside it.
+fn narrow<T: DeserializeOwned>(value: &Value) -> Vec<Segment> {
+    let mut path = vec![];
+
+    loop {
+        let Some(node) = get(value, &path) else {
+            return path;
+        };
+
+        let children = match &node.kind {
+            ValueKind::Table(table) => sorted_keys(table)
+                .into_iter()
+                .cloned()
+                .map(Segment::Key)
+                .collect(),
+            ValueKind::Array(array) => (0..array.len()).map(Segment::Index).collect(),
+            _ => vec![],
+        };
+
+        let culprit = children.into_iter().find(|child| {
+            let mut candidate = value.clone();
+            let mut child_path = path.clone();
+            child_path.push(child.clone());
+
+            if !remove(&mut candidate, &child_path) {
+                return false;
+            }
+
+            match (child, T::deserialize(candidate)) {
+                (_, Ok(_)) => true,
+                // required keys cannot be removed,
+                // but the error moving to the missing key means it was the culprit.
+                (Segment::Key(key), Err(ConfigError::NotFound(missing))) => missing == *key,
+                _ => false,
+            }
+        });
+
+        match culprit {
+            Some(child) => path.push(child),
+            None => return path,
+        }
+    }
+}
+
+fn get<'a>(value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(value, |value, segment| match (segment, &value.kind) {
+            (Segment::Key(key), ValueKind::Table(table)) => table.get(key),
+            (Segment::Index(index), ValueKind::Array(array)) => array.get(*index),
+            _ => None,
+        })
+}
+
+fn remove(value: &mut Value, path: &[Segment]) -> bool {
+    let Some((last, parent)) = path.split_last() else {
+        return false;
+    };
+
+    let parent = parent
+        .iter()
+        .try_fold(value, |value, segment| match (segment, &mut value.kind) {
+            (Segment::Key(key), ValueKind::Table(table)) => table.get_mut(key),
+            (Segment::Index(index), ValueKind::Array(array)) => array.get_mut(*index),
+            _ => None,
+        });
+
+    match (last, parent.map(|value| &mut value.kind)) {
+        (Segment::Key(key), Some(ValueKind::Table(table))) => table.remove(key).is_some(),
+        (Segment::Index(index), Some(ValueKind::Array(array))) if *index < array.len() => {
+            array.remove(*index);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn as_table(value: &Value) -> Option<&config::Map<String, Value>> {
+    match &value.kind {
+        ValueKind::Table(table) => Some(table),
+        _ => None,
+    }
+}
+
+fn sorted_keys(table: &config::Map<String, Value>) -> Vec<&String> {
+    let mut keys = table.keys().collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+/// Makes a best-effort attempt to find the byte offset of `path` in the source `text`.
+///
+/// The parsed config does not retain source positions,
+/// so keys are searched for textually in path order.
+/// Array elements are located by counting preceding `type` keys,
+/// which every module and widget has.
+///
+/// Returns the offset of the deepest segment found.
+fn locate(text: &str, root: &Value, path: &[Segment]) -> Option<usize> {
+    let mut offset = None;
+    let mut value = root;
+
+    for segment in path {
+        let from = offset.unwrap_or_default();
+
+        match (segment, &value.kind) {
+            (Segment::Key(key), ValueKind::Table(table)) => {
+                let Some(found) = find_key(text, from, key) else {
+                    break;
+                };
+
+                offset = Some(found);
+                value = &table[key];
+            }
+            (Segment::Index(index), ValueKind::Array(array)) => {
+                let Some(element) = array.get(*index) else {
+                    break;
+                };
+
+                if as_table(element).is_some_and(|table| table.contains_key("type")) {
+                    let skip = array[..*index].iter().map(count_type_keys).sum::<usize>();
+
+                    let mut found = Some(from);
+                    for _ in 0..=skip {
+                        found = found.and_then(|from| find_key(text, from, "type").map(|i| i + 1));
+                    }
+
+                    match found {
+                        Some(found) => offset = Some(found - 1),
+                        None => break,
+                    }
+                }
+
+                value = element;
+            }
+            _ => break,
+        }
+    }
+
+    offset
+}
+
+/// Counts the number of tables with a `type` key inside `value`, including itself.
+fn count_type_keys(value: &Value) -> usize {
+    match &value.kind {
+        ValueKind::Table(table) => {
+            usize::from(table.contains_key("type"))
+                + table.values().map(count_type_keys).sum::<usize>()
+        }
+        ValueKind::Array(array) => array.iter().map(count_type_keys).sum(),
+        _ => 0,
+    }
+}
+
+/// Finds the next position of `key` used as a key in any supported format,
+/// starting at byte offset `from`.
+///
+/// The key must be a whole word, optionally quoted,
+/// and be followed by an assignment (`:`, `=`), path separator (`.`) or table header end (`]`).
+fn find_key(text: &str, from: usize, key: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+
+    text.get(from..)?
+        .match_indices(key)
+        .map(|(i, _)| from + i)
+        .find(|&start| {
+            let before = text[..start].chars().next_back();
+            if before.is_some_and(is_word) {
+                return false;
+            }
+
+            let after = text[start + key.len()..]
+                .trim_start_matches(['"', '\''])
+                .trim_start();
+
+            after.starts_with([':', '=', '.', ']'])
+        })
+}
+
+/// Converts a byte offset into a 1-indexed line and column.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |i| &before[i + 1..])
+        .chars()
+        .count()
+        + 1;
+
+    (line, column)
+}
+
+#[cfg(feature = "extras")]
+impl Validator<'_> {
+    /// Walks the config alongside its JSON schema,
+    /// reporting any keys not present in the schema.
+    fn check_unknown_keys(&mut self) {
+        let schema = schemars::schema_for!(Config);
+        let schema = schema.as_value();
+
+        let mut unknown = vec![];
+        let walker = SchemaWalker { root: schema };
+        walker.walk(schema, self.root, &mut vec![], &mut unknown);
+
+        for path in unknown {
+            // allow editors to find the schema
+            if path == [Segment::Key("$schema".to_string())] {
+                continue;
+            }
+
+            self.push(
+                DiagnosticKind::UnknownKey,
+                &path,
+                "key is not used by Ironbar",
+            );
+        }
+    }
+}
+
+#[cfg(feature = "extras")]
+struct SchemaWalker<'a> {
+    root: &'a serde_json::Value,
+}
+
+#[cfg(feature = "extras")]
+impl<'a> SchemaWalker<'a> {
+    fn walk(
+        &self,
+        schema: &'a serde_json::Value,
+        value: &Value,
+        path: &mut Vec<Segment>,
+        unknown: &mut Vec<Vec<Segment>>,
+    ) {
+        let mut schemas = vec![];
+        self.expand(schema, value, &mut schemas);
+
+        match &value.kind {
+            ValueKind::Table(table) => {
+                let properties = schemas
+                    .iter()
+                    .filter_map(|schema| schema.get("properties")?.as_object())
+                    .collect::<Vec<_>>();
+
+                let additional = schemas
+                    .iter()
+                    .filter_map(|schema| schema.get("additionalProperties"))
+                    .find(|schema| schema.is_object());
+
+                // nothing is known about this object's structure
+                if properties.is_empty() && additional.is_none() {
+                    return;
+                }
+
+                for key in sorted_keys(table) {
+                    let child_schema = properties
+                        .iter()
+                        .find_map(|properties| properties.get(key))
+                        .or(additional);
+
+                    path.push(Segment::Key(key.clone()));
+                    match child_schema {
+                        Some(child_schema) => self.walk(child_schema, &table[key], path, unknown),
+                        None => unknown.push(path.clone()),
+                    }
+                    path.pop();
+                }
+            }
+            ValueKind::Array(array) => {
+                let Some(items) = schemas.iter().find_map(|schema| schema.get("items")) else {
+                    return;
+                };
+
+                for (i, element) in array.iter().enumerate() {
+                    path.push(Segment::Index(i));
+                    self.walk(items, element, path, unknown);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Flattens `schema` into the list of schemas which apply to `value`,
+    /// following references and combinators.
+    ///
+    /// For `anyOf`/`oneOf`, branches with a `const` property matching the value
+    /// (such as a module's `type`) are preferred.
+    fn expand(
+        &self,
+        schema: &'a serde_json::Value,
+        value: &Value,
+        out: &mut Vec<&'a serde_json::Value>,
+    ) {
+        out.push(schema);
+
+        if let Some(target) = schema
+            .get("$ref")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| self.root.pointer(pointer))
+        {
+            self.expand(target, value, out);
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(serde_json::Value::as_array) {
+            for schema in all {
+                self.expand(schema, value, out);
+            }
+        }
+
+        for key in ["anyOf", "oneOf"] {
+            let Some(branches) = schema.get(key).and_then(serde_json::Value::as_array) else {
+                continue;
+            };
+
+            let matching = branches
+                .iter()
+                .filter(|branch| discriminates(branch, value))
+                .collect::<Vec<_>>();
+
+            if matching.is_empty() {
+                for branch in branches {
+                    self.expand(branch, value, out);
+                }
+            } else {
+                for branch in matching {
+                    self.expand(branch, value, out);
+                }
+            }
+        }
+    }
+}
+
+/// Whether `schema` has at least one `const` property,
+/// and `value` matches all of them.
+#[cfg(feature = "extras")]
+fn discriminates(schema: &serde_json::Value, value: &Value) -> bool {
+    let (Some(properties), Some(table)) = (
+        schema
+            .get("properties")
+            .and_then(serde_json::Value::as_object),
+        as_table(value),
+    ) else {
+        return false;
+    };
+
+    let mut consts = properties
+        .iter()
+        .filter_map(|(key, schema)| Some((key, schema.get("const")?.as_str()?)))
+        .peekable();
+
+    consts.peek().is_some()
+        && consts.all(|(key, expected)| {
+            table
+                .get(key)
+                .is_some_and(|value| matches!(&value.kind, ValueKind::String(s) if s == expected))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::resolve::resolve_str;
+
+    fn validate_str(file: &str, text: &str, format: FileFormat) -> Report {
+        validate_resolved(&resolve_str(file, text, format))
+    }
+
+    #[test]
+    fn path_format() {
+        let path = [
+            Segment::Key("monitors".to_string()),
+            Segment::Key("DP-1".to_string()),
+            Segment::Index(0),
+            Segment::Key("end".to_string()),
+            Segment::Index(2),
+            Segment::Key("format".to_string()),
+        ];
+
+        assert_eq!(format_path(&path), "monitors.DP-1[0].end[2].format");
+    }
+
+    #[test]
+    fn find_key_whole_word() {
+        let text = r#"{ "format_popup": "a", "name": "format", "format": "b" }"#;
+        let offset = find_key(text, 0, "format").expect("to find key");
+        assert_eq!(&text[offset..offset + 8], "format\":");
+    }
+
+    #[test]
+    fn find_key_dotted() {
+        let text = "{ margin.top = 10 }";
+        assert_eq!(find_key(text, 0, "top"), Some(9));
+    }
+
+    #[test]
+    fn line_column_offset() {
+        let text = "{\n  foo = 1\n  bar = 2\n}";
+        let offset = text.find("bar").expect("to find bar");
+        assert_eq!(line_column(text, offset), (3, 3));
+    }
+
+    #[cfg(all(feature = "config+json", feature = "clock"))]
+    #[test]
+    fn locates_module_error() {
+        let text = r#"{
+  "end": [
+    { "type": "clock" },
+    { "type": "clock", "format": 12 }
+  ]
+}"#;
+
+        let report = validate_str("config.json", text, FileFormat::Json);
+        let diagnostic = &report.diagnostics[0];
+
+        assert_eq!(diagnostic.kind, DiagnosticKind::Invalid);
+        assert_eq!(diagnostic.path, "end[1].format");
+        assert_eq!((diagnostic.line, diagnostic.column), (Some(4), Some(25)));
+    }
+
+    #[cfg(all(feature = "config+json", feature = "clock"))]
+    #[test]
+    fn reports_unknown_module_type() {
+        let text = r#"{ "start": [{ "type": "clok" }] }"#;
+
+        let report = validate_str("config.json", text, FileFormat::Json);
+        assert_eq!(report.diagnostics[0].path, "start[0].type");
+    }
+
+    #[cfg(all(feature = "config+json", feature = "extras", feature = "clock"))]
+    #[test]
+    fn reports_unknown_keys() {
+        let text = r#"{
+  "$schema": "https://f.jstanger.dev/github/ironbar/schema.json",
+  "hieght": 32,
+  "monitors": { "DP-1": [{ "end": [{ "type": "clock", "fromat": "%H" }] }] }
+}"#;
+
+        let report = validate_str("config.json", text, FileFormat::Json);
+        let paths = report
+            .diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.kind, diagnostic.path.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            [
+                (DiagnosticKind::UnknownKey, "hieght"),
+                (DiagnosticKind::UnknownKey, "monitors.DP-1[0].end[0].fromat")
+            ]
+        );
+        assert!(!report.has_errors());
+    }
+}
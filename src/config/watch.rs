@@ -0,0 +1,133 @@
+use crate::channels::{AsyncSenderExt, MpscReceiverExt};
+use crate::spawn;
+use notify::event::ModifyKind;
+use notify::{
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Result, Watcher, recommended_watcher,
+};
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tracing::{debug, error};
+
+/// Time to wait after the last change before reloading,
+/// as editors often write files in several steps.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Watches config files for changes,
+/// calling back on the main thread once changes have settled.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    tx: mpsc::Sender<()>,
+    paths: Vec<PathBuf>,
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl ConfigWatcher {
+    pub fn new<F>(on_change: F) -> Self
+    where
+        F: Fn() + 'static,
+    {
+        let (tx, mut rx) = mpsc::channel(8);
+        let (debounced_tx, debounced_rx) = mpsc::channel(1);
+
+        spawn(async move {
+            while rx.recv().await.is_some() {
+                // wait until no further changes arrive within the debounce window
+                loop {
+                    match timeout(DEBOUNCE, rx.recv()).await {
+                        Ok(Some(())) => {}
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+
+                debounced_tx.send_expect(()).await;
+            }
+        });
+
+        debounced_rx.recv_glib((), move |(), ()| on_change());
+
+        Self {
+            tx,
+            paths: vec![],
+            watcher: None,
+        }
+    }
+
+    /// Replaces the set of watched files.
+    /// Passing an empty list stops watching.
+    pub fn watch(&mut self, paths: Vec<PathBuf>) {
+        // file watcher requires absolute paths
+        let paths = paths
+            .into_iter()
+            .map(|path| {
+                if path.is_absolute() {
+                    path
+                } else {
+                    env::current_dir().expect("to exist").join(path)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if paths == self.paths {
+            return;
+        }
+
+        self.watcher = None;
+        self.paths.clone_from(&paths);
+
+        if paths.is_empty() {
+            debug!("Stopped config file watcher");
+            return;
+        }
+
+        let tx = self.tx.clone();
+        let watched = paths.clone();
+        let watcher = recommended_watcher(move |res: Result<Event>| match res {
+            Ok(event)
+                if matches!(
+                    event.kind,
+                    EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_))
+                        | EventKind::Create(_)
+                ) =>
+            {
+                debug!("{event:?}");
+                if event.paths.iter().any(|path| watched.contains(path)) {
+                    tx.send_spawn(());
+                }
+            }
+            Err(e) => error!("Error occurred when watching config: {:?}", e),
+            _ => {}
+        });
+
+        let mut watcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                error!("Failed to create config file watcher: {err:?}");
+                return;
+            }
+        };
+
+        // watch directories rather than files,
+        // as editors commonly replace the file instead of writing to it.
+        let dirs = paths
+            .iter()
+            .filter_map(|path| path.parent())
+            .collect::<HashSet<_>>();
+
+        for dir in dirs {
+            if let Err(err) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+                error!(
+                    "Failed to start config file watcher on '{}': {err:?}",
+                    dir.display()
+                );
+            }
+        }
+
+        debug!("Installed config file watcher on {paths:?}");
+        self.watcher = Some(watcher);
+    }
+}
@@ -1,21 +1,23 @@
-#[cfg(feature = "ipc")]
-use crate::Ironbar;
 use crate::channels::{AsyncSenderExt, Dependency, MpscReceiverExt};
+#[cfg(feature = "ipc")]
+use crate::dynamic_value::expression::{Expression, is_truthy};
 use crate::script::Script;
 use crate::spawn;
 use cfg_if::cfg_if;
 use serde::Deserialize;
 use tokio::sync::mpsc;
+#[cfg(feature = "ipc")]
+use tracing::error;
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum DynamicBool {
-    /// Either a script or variable, to be determined.
+    /// Either a script or expression, to be determined.
     Unknown(String),
     Script(Script),
     #[cfg(feature = "ipc")]
-    Variable(Box<str>),
+    Expression(Box<str>),
 }
 
 impl DynamicBool {
@@ -27,10 +29,10 @@ impl DynamicBool {
     {
         let value = match self {
             Self::Unknown(input) => {
-                if input.starts_with('#') {
+                if input.starts_with('#') || input.starts_with("${") {
                     cfg_if! {
                         if #[cfg(feature = "ipc")] {
-                            Self::Variable(input.into())
+                            Self::Expression(input.into())
                         } else {
                             Self::Unknown(input)
                         }
@@ -57,15 +59,19 @@ impl DynamicBool {
                         .await;
                 }
                 #[cfg(feature = "ipc")]
-                DynamicBool::Variable(variable) => {
-                    let variable_manager = Ironbar::variable_manager();
-
-                    let variable_name = variable[1..].into(); // remove hash
-                    let mut rx = variable_manager.subscribe(variable_name);
+                DynamicBool::Expression(input) => {
+                    let input = input
+                        .strip_prefix("${")
+                        .and_then(|input| input.strip_suffix('}'))
+                        .unwrap_or(&*input);
 
-                    while let Ok(value) = rx.recv().await {
-                        let has_value = value.is_some_and(|s| is_truthy(&s));
-                        tx.send_expect(has_value).await;
+                    match input.parse::<Expression>() {
+                        Ok(expression) => {
+                            expression
+                                .watch(|value| tx.send_spawn(is_truthy(&value)))
+                                .await;
+                        }
+                        Err(err) => error!("Invalid expression '{input}': {err}"),
                     }
                 }
                 DynamicBool::Unknown(_) => unreachable!(),
@@ -73,12 +79,3 @@ impl DynamicBool {
         });
     }
 }
-
-/// Check if a string ironvar is 'truthy',
-/// i.e should be evaluated to true.
-///
-/// This loosely follows the common JavaScript cases.
-#[cfg(feature = "ipc")]
-fn is_truthy(string: &str) -> bool {
-    !(string.is_empty() || string == "0" || string == "false")
-}
@@ -1,19 +1,25 @@
 #[cfg(feature = "ipc")]
 use crate::Ironbar;
 use crate::channels::{AsyncSenderExt, Dependency, MpscReceiverExt};
+#[cfg(feature = "ipc")]
+use crate::dynamic_value::Expression;
+#[cfg(feature = "ipc")]
+use crate::ironvar::{VariablePath, value_to_string};
 use crate::script::{OutputStream, Script};
 use crate::{arc_mut, lock, spawn};
 use tokio::sync::mpsc;
 
 /// A segment of a dynamic string,
-/// containing either a static string
-/// or a script.
+/// containing either a static string,
+/// a script, a variable or an expression.
 #[derive(Debug)]
 enum DynamicStringSegment {
     Static(String),
     Script(Script),
     #[cfg(feature = "ipc")]
     Variable(Box<str>),
+    #[cfg(feature = "ipc")]
+    Expression(Expression),
 }
 
 /// Creates a new dynamic string, based off the input template.
@@ -73,11 +79,18 @@ where
                 lock!(label_parts).push(String::new());
 
                 spawn(async move {
+                    let path = VariablePath::parse(&name);
+
                     let variable_manager = Ironbar::variable_manager();
-                    let mut rx = variable_manager.subscribe(name);
+                    let mut rx = variable_manager.subscribe(path.key.clone());
 
                     while let Ok(value) = rx.recv().await {
                         if let Some(value) = value {
+                            let value = path
+                                .resolve(&value)
+                                .map(value_to_string)
+                                .unwrap_or_default();
+
                             let mut label_parts = lock!(label_parts);
 
                             let _: String = std::mem::replace(&mut label_parts[i], value);
@@ -88,6 +101,28 @@ where
                     }
                 });
             }
+            #[cfg(feature = "ipc")]
+            DynamicStringSegment::Expression(expression) => {
+                let tx = tx.clone();
+                let label_parts = label_parts.clone();
+
+                // insert blank value to preserve segment order
+                lock!(label_parts).push(String::new());
+
+                spawn(async move {
+                    expression
+                        .watch(|value| {
+                            let mut label_parts = lock!(label_parts);
+
+                            let _: String =
+                                std::mem::replace(&mut label_parts[i], value_to_string(&value));
+
+                            let string = label_parts.join("");
+                            tx.send_spawn(string);
+                        })
+                        .await;
+                });
+            }
         }
     }
 
@@ -103,7 +138,7 @@ where
 /// Parses the input string into static and dynamic segments
 fn parse_input(input: &str) -> (Vec<DynamicStringSegment>, bool) {
     // short-circuit parser if it's all static
-    if !input.contains("{{") && !input.contains('#') {
+    if !input.contains("{{") && !input.contains('#') && !input.contains("${") {
         return (vec![DynamicStringSegment::Static(input.to_string())], true);
     }
 
@@ -122,6 +157,8 @@ fn parse_input(input: &str) -> (Vec<DynamicStringSegment>, bool) {
             Some(['#', '#']) => (DynamicStringSegment::Static("#".to_string()), 2),
             #[cfg(feature = "ipc")]
             Some(['#', _]) => parse_variable(&chars),
+            #[cfg(feature = "ipc")]
+            Some(['$', '{']) => parse_expression(&chars),
             _ => parse_static(&chars),
         };
 
@@ -151,14 +188,25 @@ fn parse_script(chars: &[char]) -> (DynamicStringSegment, usize) {
     (DynamicStringSegment::Script(script), len)
 }
 
+/// Parses a variable, including any path into its value.
+///
+/// A `.` is only treated as part of the path
+/// when followed by another valid character,
+/// so that variables can end a sentence.
 #[cfg(feature = "ipc")]
 fn parse_variable(chars: &[char]) -> (DynamicStringSegment, usize) {
     const SKIP_HASH: usize = 1;
 
+    let is_key_char = |c: &char| c.is_ascii_alphanumeric() || c == &'_' || c == &'-';
+
     let str = chars
         .iter()
+        .enumerate()
         .skip(1)
-        .take_while(|&c| c.is_ascii_alphanumeric() || c == &'_' || c == &'-')
+        .take_while(|&(i, c)| {
+            is_key_char(c) || (c == &'.' && i > 1 && chars.get(i + 1).is_some_and(is_key_char))
+        })
+        .map(|(_, c)| c)
         .collect::<String>();
 
     let len = str.chars().count() + SKIP_HASH;
@@ -167,10 +215,52 @@ fn parse_variable(chars: &[char]) -> (DynamicStringSegment, usize) {
     (DynamicStringSegment::Variable(value), len)
 }
 
+/// Parses an expression inside `${...}`.
+///
+/// The expression ends at the first `}` outside of a quoted string.
+/// If the expression is invalid, the error is logged
+/// and the segment is kept as static text.
+#[cfg(feature = "ipc")]
+fn parse_expression(chars: &[char]) -> (DynamicStringSegment, usize) {
+    const SKIP_BRACES: usize = 3; // `${` and `}`
+
+    let mut quote = None;
+    let mut escaped = false;
+
+    let str = chars
+        .iter()
+        .skip(2)
+        .take_while(|&&c| {
+            match (quote, c) {
+                _ if escaped => escaped = false,
+                (Some(_), '\\') => escaped = true,
+                (Some(q), c) if c == q => quote = None,
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '}') => return false,
+                _ => {}
+            }
+            true
+        })
+        .collect::<String>();
+
+    let len = (str.chars().count() + SKIP_BRACES).min(chars.len());
+
+    match str.parse() {
+        Ok(expression) => (DynamicStringSegment::Expression(expression), len),
+        Err(err) => {
+            tracing::error!("Invalid expression '{str}': {err}");
+            (
+                DynamicStringSegment::Static(chars[..len].iter().collect()),
+                len,
+            )
+        }
+    }
+}
+
 fn parse_static(chars: &[char]) -> (DynamicStringSegment, usize) {
     let mut str = chars
         .windows(2)
-        .take_while(|&win| win != ['{', '{'] && win[0] != '#')
+        .take_while(|&win| win != ['{', '{'] && win[0] != '#' && win != ['$', '{'])
         .map(|w| w[0])
         .collect::<String>();
 
@@ -230,6 +320,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_variable_path() {
+        const INPUT: &str = "status: #build.result.0.status.";
+        let (tokens, _) = parse_input(INPUT);
+
+        assert_eq!(tokens.len(), 3);
+        assert!(matches!(&tokens[0], DynamicStringSegment::Static(str) if str == "status: "));
+        assert!(
+            matches!(&tokens[1], DynamicStringSegment::Variable(name) if name.to_string() == "build.result.0.status")
+        );
+        assert!(matches!(&tokens[2], DynamicStringSegment::Static(str) if str == "."));
+    }
+
+    #[test]
+    fn test_expression() {
+        const INPUT: &str = "CPU: ${round(#sysinfo.cpu_percent) + '%'} {{echo hi}}";
+        let (tokens, _) = parse_input(INPUT);
+
+        assert_eq!(tokens.len(), 4);
+        assert!(matches!(&tokens[0], DynamicStringSegment::Static(str) if str == "CPU: "));
+        assert!(matches!(&tokens[1], DynamicStringSegment::Expression(_)));
+        assert!(matches!(&tokens[2], DynamicStringSegment::Static(str) if str == " "));
+        assert!(
+            matches!(&tokens[3], DynamicStringSegment::Script(script) if script.cmd == "echo hi")
+        );
+    }
+
+    #[test]
+    fn test_expression_with_brace_in_string() {
+        const INPUT: &str = "${'}' + #a}!";
+        let (tokens, _) = parse_input(INPUT);
+
+        assert_eq!(tokens.len(), 2);
+        assert!(matches!(&tokens[0], DynamicStringSegment::Expression(_)));
+        assert!(matches!(&tokens[1], DynamicStringSegment::Static(str) if str == "!"));
+    }
+
+    #[test]
+    fn test_invalid_expression() {
+        const INPUT: &str = "${1 +}";
+        let (tokens, _) = parse_input(INPUT);
+
+        assert_eq!(tokens.len(), 1);
+        assert!(matches!(&tokens[0], DynamicStringSegment::Static(str) if str == INPUT));
+    }
+
     #[test]
     fn test_static_script() {
         const INPUT: &str = "hello {{echo world}}";
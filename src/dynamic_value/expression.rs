@@ -0,0 +1,814 @@
+use crate::ironvar::{VariablePath, value_to_string};
+use crate::{Ironbar, spawn};
+use color_eyre::{Report, Result};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tokio::sync::mpsc;
+
+/// A parsed expression, which can be evaluated
+/// against the current ironvar values.
+///
+/// See the [dynamic values](dynamic-values#expressions) docs for the syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression(Expr);
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Variable(VariablePath),
+    Not(Box<Expr>),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    If,
+    Default,
+    Upper,
+    Lower,
+    Trim,
+    Len,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Replace,
+    Substr,
+    Truncate,
+    Round,
+    Floor,
+    Ceil,
+    Abs,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name {
+            "if" => Self::If,
+            "default" => Self::Default,
+            "upper" => Self::Upper,
+            "lower" => Self::Lower,
+            "trim" => Self::Trim,
+            "len" => Self::Len,
+            "contains" => Self::Contains,
+            "starts_with" => Self::StartsWith,
+            "ends_with" => Self::EndsWith,
+            "replace" => Self::Replace,
+            "substr" => Self::Substr,
+            "truncate" => Self::Truncate,
+            "round" => Self::Round,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "abs" => Self::Abs,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            _ => return None,
+        };
+
+        Some(function)
+    }
+
+    /// The minimum and maximum number of arguments accepted.
+    fn arity(self) -> (usize, usize) {
+        match self {
+            Self::Upper | Self::Lower | Self::Trim | Self::Len => (1, 1),
+            Self::Floor | Self::Ceil | Self::Abs => (1, 1),
+            Self::Round => (1, 2),
+            Self::Default | Self::Contains | Self::StartsWith | Self::EndsWith => (2, 2),
+            Self::Truncate => (2, 2),
+            Self::Substr => (2, 3),
+            Self::If | Self::Replace => (3, 3),
+            Self::Min | Self::Max => (1, usize::MAX),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Variable(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+    Question,
+    Colon,
+}
+
+/// Operators in the order they should be matched,
+/// so that two-character operators are not split.
+const OPERATORS: [&str; 14] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!",
+];
+
+/// Checks whether `c` can be part of a variable name.
+///
+/// As with ironvar keys, this includes `-`,
+/// so `#a-1` is the variable `a-1`.
+/// Subtracting from a variable needs spaces around the operator (`#a - 1`).
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' | ')' | ',' | '?' | ':' => {
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    '?' => Token::Question,
+                    _ => Token::Colon,
+                });
+                i += 1;
+            }
+            '\'' | '"' => {
+                let mut str = String::new();
+                i += 1;
+
+                loop {
+                    match chars.get(i) {
+                        Some(&ch) if ch == c => break,
+                        Some('\\') => {
+                            if let Some(&next) = chars.get(i + 1) {
+                                str.push(next);
+                            }
+                            i += 2;
+                        }
+                        Some(&ch) => {
+                            str.push(ch);
+                            i += 1;
+                        }
+                        None => return Err(Report::msg("Unterminated string")),
+                    }
+                }
+
+                tokens.push(Token::String(str));
+                i += 1;
+            }
+            '#' => {
+                let start = i + 1;
+                i = start;
+
+                while let Some(&ch) = chars.get(i) {
+                    let is_path_separator = ch == '.'
+                        && i > start
+                        && chars.get(i + 1).is_some_and(|&next| is_key_char(next));
+
+                    if is_key_char(ch) || is_path_separator {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+
+                if i == start {
+                    return Err(Report::msg("Expected variable name after `#`"));
+                }
+
+                tokens.push(Token::Variable(chars[start..i].iter().collect()));
+            }
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .is_some_and(|ch| ch.is_ascii_digit() || *ch == '.')
+                {
+                    i += 1;
+                }
+
+                let str = chars[start..i].iter().collect::<String>();
+                let number = str
+                    .parse()
+                    .map_err(|_| Report::msg(format!("Invalid number `{str}`")))?;
+
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while chars
+                    .get(i)
+                    .is_some_and(|ch| ch.is_alphanumeric() || *ch == '_')
+                {
+                    i += 1;
+                }
+
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            _ => {
+                let op = OPERATORS
+                    .iter()
+                    .find(|op| {
+                        op.chars()
+                            .enumerate()
+                            .all(|(offset, ch)| chars.get(i + offset) == Some(&ch))
+                    })
+                    .ok_or_else(|| Report::msg(format!("Unexpected character `{c}`")))?;
+
+                tokens.push(Token::Op(op));
+                i += op.len();
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the token list.
+///
+/// Each method parses one precedence level,
+/// from lowest (conditional) to highest (primary).
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, name: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(Report::msg(format!("Expected `{name}`")))
+        }
+    }
+
+    fn conditional(&mut self) -> Result<Expr> {
+        let condition = self.binary(0)?;
+
+        if self.eat(&Token::Question) {
+            let then = self.conditional()?;
+            self.expect(&Token::Colon, ":")?;
+            let otherwise = self.conditional()?;
+
+            Ok(Expr::Conditional(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ))
+        } else {
+            Ok(condition)
+        }
+    }
+
+    /// Parses binary operators, starting at precedence `level`.
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        const LEVELS: [&[(&str, BinaryOp)]; 6] = [
+            &[("||", BinaryOp::Or)],
+            &[("&&", BinaryOp::And)],
+            &[("==", BinaryOp::Eq), ("!=", BinaryOp::Ne)],
+            &[
+                ("<", BinaryOp::Lt),
+                ("<=", BinaryOp::Le),
+                (">", BinaryOp::Gt),
+                (">=", BinaryOp::Ge),
+            ],
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+            &[
+                ("*", BinaryOp::Mul),
+                ("/", BinaryOp::Div),
+                ("%", BinaryOp::Rem),
+            ],
+        ];
+
+        let Some(operators) = LEVELS.get(level) else {
+            return self.unary();
+        };
+
+        let mut lhs = self.binary(level + 1)?;
+
+        while let Some(Token::Op(token)) = self.peek()
+            && let Some(&(_, op)) = operators.iter().find(|(op, _)| op == token)
+        {
+            self.pos += 1;
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Op("!")) {
+            Ok(Expr::Not(Box::new(self.unary()?)))
+        } else if self.eat(&Token::Op("-")) {
+            Ok(Expr::Negate(Box::new(self.unary()?)))
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Literal(number_value(number))),
+            Some(Token::String(str)) => Ok(Expr::Literal(Value::String(str))),
+            Some(Token::Variable(name)) => Ok(Expr::Variable(VariablePath::parse(&name))),
+            Some(Token::LParen) => {
+                let expr = self.conditional()?;
+                self.expect(&Token::RParen, ")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(name)) if self.eat(&Token::LParen) => self.call(&name),
+            Some(Token::Ident(name)) => Ok(Expr::Literal(match name.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                // bare words are treated as strings, so `#status == ok` works.
+                _ => Value::String(name),
+            })),
+            Some(token) => Err(Report::msg(format!("Unexpected token {token:?}"))),
+            None => Err(Report::msg("Unexpected end of expression")),
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr> {
+        let function = Function::from_name(name)
+            .ok_or_else(|| Report::msg(format!("Unknown function `{name}`")))?;
+
+        let mut args = vec![];
+        if !self.eat(&Token::RParen) {
+            loop {
+                args.push(self.conditional()?);
+                if self.eat(&Token::RParen) {
+                    break;
+                }
+                self.expect(&Token::Comma, ",")?;
+            }
+        }
+
+        let (min, max) = function.arity();
+        if args.len() < min || args.len() > max {
+            return Err(Report::msg(format!(
+                "Wrong number of arguments for `{name}`"
+            )));
+        }
+
+        Ok(Expr::Call(function, args))
+    }
+}
+
+impl FromStr for Expression {
+    type Err = Report;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+
+        let expr = parser.conditional()?;
+
+        if let Some(token) = parser.peek() {
+            return Err(Report::msg(format!("Unexpected token {token:?}")));
+        }
+
+        Ok(Self(expr))
+    }
+}
+
+impl Expression {
+    /// Gets the keys of all variables referenced in the expression.
+    pub fn variables(&self) -> HashSet<Box<str>> {
+        fn collect(expr: &Expr, keys: &mut HashSet<Box<str>>) {
+            match expr {
+                Expr::Literal(_) => {}
+                Expr::Variable(path) => {
+                    keys.insert(path.key.clone());
+                }
+                Expr::Not(expr) | Expr::Negate(expr) => collect(expr, keys),
+                Expr::Binary(_, lhs, rhs) => {
+                    collect(lhs, keys);
+                    collect(rhs, keys);
+                }
+                Expr::Conditional(condition, then, otherwise) => {
+                    collect(condition, keys);
+                    collect(then, keys);
+                    collect(otherwise, keys);
+                }
+                Expr::Call(_, args) => args.iter().for_each(|arg| collect(arg, keys)),
+            }
+        }
+
+        let mut keys = HashSet::new();
+        collect(&self.0, &mut keys);
+        keys
+    }
+
+    /// Evaluates the expression,
+    /// using `values` to look up the value of each variable by key.
+    ///
+    /// Evaluation never fails: invalid operations return `null`.
+    pub fn evaluate(&self, values: &HashMap<Box<str>, Option<Value>>) -> Value {
+        evaluate(&self.0, values)
+    }
+
+    /// Evaluates the expression each time one of its variables changes,
+    /// calling `f` with the result whenever it differs from the previous one.
+    ///
+    /// Namespaces are watched like any other variable,
+    /// so `#sysinfo.memory_percent` updates as the namespace does.
+    ///
+    /// Expressions without variables are evaluated once.
+    pub async fn watch<F>(self, mut f: F)
+    where
+        F: FnMut(Value),
+    {
+        let keys = self.variables();
+
+        let (tx, mut rx) = mpsc::channel(32);
+        let variable_manager = Ironbar::variable_manager();
+
+        for key in &keys {
+            let mut var_rx = variable_manager.subscribe(key.clone());
+            let tx = tx.clone();
+            let key = key.clone();
+
+            spawn(async move {
+                while let Ok(value) = var_rx.recv().await {
+                    if tx.send((key.clone(), value)).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        drop(tx);
+
+        let mut values = HashMap::new();
+
+        if keys.is_empty() {
+            f(self.evaluate(&values));
+            return;
+        }
+
+        let mut last = None;
+        while let Some((key, value)) = rx.recv().await {
+            values.insert(key, value);
+
+            // each subscription sends its current value immediately,
+            // so wait for all of them before the first evaluation.
+            if values.len() < keys.len() {
+                continue;
+            }
+
+            let result = self.evaluate(&values);
+            if last.as_ref() != Some(&result) {
+                last = Some(result.clone());
+                f(result);
+            }
+        }
+    }
+}
+
+fn evaluate(expr: &Expr, values: &HashMap<Box<str>, Option<Value>>) -> Value {
+    let eval = |expr: &Expr| evaluate(expr, values);
+
+    match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Variable(path) => values
+            .get(&path.key)
+            .and_then(Option::as_ref)
+            .and_then(|value| path.resolve(value))
+            .cloned()
+            .unwrap_or(Value::Null),
+        Expr::Not(expr) => Value::Bool(!is_truthy(&eval(expr))),
+        Expr::Negate(expr) => as_number(&eval(expr)).map_or(Value::Null, |n| number_value(-n)),
+        Expr::Binary(BinaryOp::Or, lhs, rhs) => {
+            let lhs = eval(lhs);
+            if is_truthy(&lhs) { lhs } else { eval(rhs) }
+        }
+        Expr::Binary(BinaryOp::And, lhs, rhs) => {
+            let lhs = eval(lhs);
+            if is_truthy(&lhs) { eval(rhs) } else { lhs }
+        }
+        Expr::Binary(op, lhs, rhs) => binary(*op, &eval(lhs), &eval(rhs)),
+        Expr::Conditional(condition, then, otherwise) => {
+            if is_truthy(&eval(condition)) {
+                eval(then)
+            } else {
+                eval(otherwise)
+            }
+        }
+        Expr::Call(Function::If, args) => {
+            if is_truthy(&eval(&args[0])) {
+                eval(&args[1])
+            } else {
+                eval(&args[2])
+            }
+        }
+        Expr::Call(function, args) => {
+            let args = args.iter().map(eval).collect::<Vec<_>>();
+            call(*function, &args).unwrap_or(Value::Null)
+        }
+    }
+}
+
+fn binary(op: BinaryOp, lhs: &Value, rhs: &Value) -> Value {
+    use BinaryOp::*;
+
+    let numbers = as_number(lhs).zip(as_number(rhs));
+
+    match op {
+        Eq | Ne | Lt | Le | Gt | Ge => {
+            let ordering = match numbers {
+                Some((lhs, rhs)) => lhs.partial_cmp(&rhs),
+                None => Some(value_to_string(lhs).cmp(&value_to_string(rhs))),
+            };
+
+            Value::Bool(ordering.is_some_and(|ordering| match op {
+                Eq => ordering.is_eq(),
+                Ne => ordering.is_ne(),
+                Lt => ordering.is_lt(),
+                Le => ordering.is_le(),
+                Gt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        Add => match numbers {
+            Some((lhs, rhs)) => number_value(lhs + rhs),
+            None => Value::String(value_to_string(lhs) + &value_to_string(rhs)),
+        },
+        Sub | Mul | Div | Rem => {
+            let Some((lhs, rhs)) = numbers else {
+                return Value::Null;
+            };
+
+            match op {
+                Sub => number_value(lhs - rhs),
+                Mul => number_value(lhs * rhs),
+                _ if rhs == 0.0 => Value::Null,
+                Div => number_value(lhs / rhs),
+                _ => number_value(lhs % rhs),
+            }
+        }
+        Or | And => unreachable!("handled in `evaluate` to short-circuit"),
+    }
+}
+
+fn call(function: Function, args: &[Value]) -> Option<Value> {
+    let str = |i: usize| args.get(i).map(value_to_string);
+    let num = |i: usize| args.get(i).and_then(as_number);
+
+    let value = match function {
+        Function::If => unreachable!("handled in `evaluate` to short-circuit"),
+        Function::Default => {
+            if matches!(&args[0], Value::Null) || args[0] == Value::String(String::new()) {
+                args[1].clone()
+            } else {
+                args[0].clone()
+            }
+        }
+        Function::Upper => Value::String(str(0)?.to_uppercase()),
+        Function::Lower => Value::String(str(0)?.to_lowercase()),
+        Function::Trim => Value::String(str(0)?.trim().to_string()),
+        Function::Len => Value::from(match &args[0] {
+            Value::Array(items) => items.len(),
+            Value::Object(map) => map.len(),
+            value => value_to_string(value).chars().count(),
+        }),
+        Function::Contains => Value::Bool(match &args[0] {
+            Value::Array(items) => items.contains(&args[1]),
+            value => value_to_string(value).contains(&str(1)?),
+        }),
+        Function::StartsWith => Value::Bool(str(0)?.starts_with(&str(1)?)),
+        Function::EndsWith => Value::Bool(str(0)?.ends_with(&str(1)?)),
+        Function::Replace => Value::String(str(0)?.replace(&str(1)?, &str(2)?)),
+        Function::Substr => {
+            let str = str(0)?;
+            let chars = str.chars().skip(as_index(num(1)?));
+
+            Value::String(match num(2) {
+                Some(len) => chars.take(as_index(len)).collect(),
+                None => chars.collect(),
+            })
+        }
+        Function::Truncate => {
+            let str = str(0)?;
+            let max = as_index(num(1)?);
+
+            if str.chars().count() > max {
+                let mut str = str.chars().take(max.saturating_sub(1)).collect::<String>();
+                str.push('…');
+                Value::String(str)
+            } else {
+                Value::String(str)
+            }
+        }
+        Function::Round => {
+            let factor = 10_f64.powf(num(1).unwrap_or_default().trunc());
+            number_value((num(0)? * factor).round() / factor)
+        }
+        Function::Floor => number_value(num(0)?.floor()),
+        Function::Ceil => number_value(num(0)?.ceil()),
+        Function::Abs => number_value(num(0)?.abs()),
+        Function::Min => number_value(
+            (0..args.len())
+                .map(num)
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .fold(f64::INFINITY, f64::min),
+        ),
+        Function::Max => number_value(
+            (0..args.len())
+                .map(num)
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .fold(f64::NEG_INFINITY, f64::max),
+        ),
+    };
+
+    Some(value)
+}
+
+/// Gets a value as a number.
+/// Strings are parsed, so values set from the CLI can be used in arithmetic.
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(str) => str.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Converts a number to a non-negative index,
+/// discarding any fractional part.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn as_index(number: f64) -> usize {
+    number.max(0.0) as usize
+}
+
+/// Creates a JSON number,
+/// using an integer where possible so that it is displayed without a decimal point.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn number_value(number: f64) -> Value {
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        Value::from(number as i64)
+    } else {
+        serde_json::Number::from_f64(number).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// Check if a value is 'truthy',
+/// i.e should be evaluated to true.
+///
+/// This loosely follows the common JavaScript cases,
+/// additionally treating the strings `0` and `false` as false.
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64().is_some_and(|number| number != 0.0),
+        Value::String(string) => !(string.is_empty() || string == "0" || string == "false"),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(input: &str, values: &[(&str, Value)]) -> Value {
+        let values = values
+            .iter()
+            .map(|(key, value)| (Box::from(*key), Some(value.clone())))
+            .collect();
+
+        input
+            .parse::<Expression>()
+            .expect("to be valid")
+            .evaluate(&values)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(eval("1 + 2 * 3", &[]), json!(7));
+        assert_eq!(eval("(1 + 2) * 3", &[]), json!(9));
+        assert_eq!(eval("7 / 2", &[]), json!(3.5));
+        assert_eq!(eval("7 % 4 - -1", &[]), json!(4));
+        assert_eq!(eval("1 / 0", &[]), Value::Null);
+        assert_eq!(eval("#a + 1", &[("a", json!("41"))]), json!(42));
+        assert_eq!(eval("'a' + 1", &[]), json!("a1"));
+        assert_eq!(eval("#a - 1", &[("a", json!(2))]), json!(1));
+        assert_eq!(
+            eval("#a-1", &[("a", json!(2)), ("a-1", json!(5))]),
+            json!(5)
+        );
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let values = [("cpu", json!({ "percent": 85.5 })), ("dnd", json!("false"))];
+
+        assert_eq!(eval("#cpu.percent > 80 && !#dnd", &values), json!(true));
+        assert_eq!(eval("#cpu.percent <= 80 || #dnd", &values), json!("false"));
+        assert_eq!(
+            eval("#status == ok", &[("status", json!("ok"))]),
+            json!(true)
+        );
+        assert_eq!(
+            eval("#status != 'ok'", &[("status", json!("ok"))]),
+            json!(false)
+        );
+        assert_eq!(eval("'10' == 10.0", &[]), json!(true));
+        assert_eq!(eval("#missing == null", &[]), json!(true));
+    }
+
+    #[test]
+    fn test_conditionals() {
+        assert_eq!(
+            eval("#a > 1 ? 'big' : 'small'", &[("a", json!(2))]),
+            json!("big")
+        );
+        assert_eq!(eval("if(#a, 'yes', 'no')", &[]), json!("no"));
+        assert_eq!(eval("#name || 'anon'", &[]), json!("anon"));
+        assert_eq!(eval("1 ? 2 ? 3 : 4 : 5", &[]), json!(3));
+    }
+
+    #[test]
+    fn test_functions() {
+        assert_eq!(eval("upper(#s)", &[("s", json!("abc"))]), json!("ABC"));
+        assert_eq!(eval("len(#s)", &[("s", json!([1, 2]))]), json!(2));
+        assert_eq!(eval("contains('hello', 'ell')", &[]), json!(true));
+        assert_eq!(eval("replace('a-b', '-', '+')", &[]), json!("a+b"));
+        assert_eq!(eval("substr('hello', 1, 3)", &[]), json!("ell"));
+        assert_eq!(eval("truncate('hello world', 6)", &[]), json!("hello…"));
+        assert_eq!(eval("round(2.71828, 2)", &[]), json!(2.72));
+        assert_eq!(eval("max(1, 5, 3)", &[]), json!(5));
+        assert_eq!(eval("default(#x, 'none')", &[]), json!("none"));
+    }
+
+    #[test]
+    fn test_variables() {
+        let expression = "#a.b > 1 && upper(#c) == #a.d"
+            .parse::<Expression>()
+            .expect("to be valid");
+
+        let mut keys = expression.variables().into_iter().collect::<Vec<_>>();
+        keys.sort();
+
+        assert_eq!(keys, [Box::from("a"), Box::from("c")]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("1 +".parse::<Expression>().is_err());
+        assert!("(1".parse::<Expression>().is_err());
+        assert!("nope(1)".parse::<Expression>().is_err());
+        assert!("upper(1, 2)".parse::<Expression>().is_err());
+        assert!("'abc".parse::<Expression>().is_err());
+        assert!("1 2".parse::<Expression>().is_err());
+        assert!("# + 1".parse::<Expression>().is_err());
+    }
+}
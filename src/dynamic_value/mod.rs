@@ -2,6 +2,10 @@
 
 mod dynamic_bool;
 mod dynamic_string;
+#[cfg(feature = "ipc")]
+mod expression;
 
 pub use dynamic_bool::DynamicBool;
 pub use dynamic_string::dynamic_string;
+#[cfg(feature = "ipc")]
+pub use expression::Expression;
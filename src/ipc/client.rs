@@ -1,5 +1,5 @@
 use super::Ipc;
-use crate::ipc::{Command, Response};
+use crate::ipc::{Command, Event, Response};
 use color_eyre::Result;
 use color_eyre::{Help, Report};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -9,6 +9,57 @@ impl Ipc {
     /// Sends a command to the IPC server.
     /// The server response is returned.
     pub async fn send(&self, command: Command, debug: bool) -> Result<Response> {
+        let mut reader = self.connect(&command, debug).await?;
+
+        let mut read_buffer = String::new();
+        let bytes = reader.read_line(&mut read_buffer).await?;
+
+        let response = serde_json::from_str(&read_buffer[..bytes])?;
+        Ok(response)
+    }
+
+    /// Sends a subscription command to the IPC server,
+    /// and runs `f` for each event streamed back.
+    ///
+    /// The initial server response is returned
+    /// once the server closes the connection,
+    /// or immediately if it is an error.
+    pub async fn subscribe<F>(&self, command: Command, debug: bool, mut f: F) -> Result<Response>
+    where
+        F: FnMut(Event),
+    {
+        let mut reader = self.connect(&command, debug).await?;
+
+        let mut read_buffer = String::new();
+        let bytes = reader.read_line(&mut read_buffer).await?;
+
+        let response = serde_json::from_str(&read_buffer[..bytes])?;
+        if matches!(response, Response::Err { .. }) {
+            return Ok(response);
+        }
+
+        loop {
+            read_buffer.clear();
+            let bytes = reader.read_line(&mut read_buffer).await?;
+
+            if bytes == 0 {
+                break;
+            }
+
+            if debug {
+                eprintln!("EVENT JSON: {}", read_buffer.trim_end());
+            }
+
+            let event = serde_json::from_str(&read_buffer[..bytes])?;
+            f(event);
+        }
+
+        Ok(response)
+    }
+
+    /// Connects to the IPC server and writes the command.
+    /// Returns a reader for the server's response.
+    async fn connect(&self, command: &Command, debug: bool) -> Result<BufReader<UnixStream>> {
         let mut stream = match UnixStream::connect(&self.path).await {
             Ok(stream) => Ok(stream),
             Err(err) => Err(Report::new(err)
@@ -16,20 +67,15 @@ impl Ipc {
                 .suggestion("Is Ironbar running?")),
         }?;
 
-        let mut write_buffer = serde_json::to_vec(&command)?;
+        let mut write_buffer = serde_json::to_vec(command)?;
 
         if debug {
-            eprintln!("REQUEST JSON: {}", serde_json::to_string(&command)?);
+            eprintln!("REQUEST JSON: {}", serde_json::to_string(command)?);
         }
 
         write_buffer.push(b'\n');
         stream.write_all(&write_buffer).await?;
 
-        let mut read_buffer = String::new();
-        let mut reader = BufReader::new(stream);
-        let bytes = reader.read_line(&mut read_buffer).await?;
-
-        let response = serde_json::from_str(&read_buffer[..bytes])?;
-        Ok(response)
+        Ok(BufReader::new(stream))
     }
 }
@@ -1,3 +1,5 @@
+use super::EventType;
+use crate::modules::ModuleLocation;
 use clap::ArgAction;
 use std::path::PathBuf;
 
@@ -16,6 +18,25 @@ pub enum Command {
     /// Reload the config.
     Reload,
 
+    /// Get the tree of running bars and their modules.
+    Tree,
+
+    /// List the running Ironbar instances.
+    ///
+    /// When run from the CLI, instances are found locally
+    /// and Ironbar does not need to be running.
+    Instances,
+
+    /// Validate a config file, reporting any errors found.
+    ///
+    /// When run from the CLI, the config is checked locally
+    /// and Ironbar does not need to be running.
+    Check {
+        /// Path to the config file.
+        /// Defaults to the config in use.
+        path: Option<PathBuf>,
+    },
+
     /// Get and set reactive Ironvar values.
     #[command(subcommand)]
     Var(IronvarCommand),
@@ -23,9 +44,60 @@ pub enum Command {
     /// Interact with a specific bar.
     Bar(BarCommand),
 
+    /// Send an action to all modules with the given name.
+    /// The available actions depend on the module type.
+    Module {
+        /// The configured name of the module.
+        name: String,
+        /// The action to perform.
+        action: String,
+        /// Arguments for the action.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+
     /// Load stylesheets and dynamically add/remove classes
     #[command(subcommand)]
     Style(StyleCommand),
+
+    /// Control `script` modules.
+    #[command(subcommand)]
+    Script(ScriptCommand),
+
+    /// Subscribe to a stream of events.
+    /// The connection is kept open, and each event is written as a single line of JSON.
+    Subscribe {
+        /// The types of event to subscribe to.
+        /// If none are specified, all events are streamed.
+        #[arg(value_enum)]
+        #[serde(default)]
+        events: Vec<EventType>,
+    },
+
+    /// Run multiple commands sequentially in a single request.
+    /// Commands are run in order, without any other commands running in between.
+    /// Every command is checked before any are run,
+    /// so a batch containing an invalid command changes nothing.
+    /// If a command still fails while running, the batch stops there.
+    ///
+    /// When run from the CLI, commands are read from `stdin`, one per line.
+    /// Each line can be either CLI arguments (eg `var set foo bar`) or a JSON command.
+    Batch {
+        /// The commands to run.
+        #[arg(skip)]
+        commands: Vec<Command>,
+    },
+}
+
+impl Command {
+    /// Whether the command keeps the connection open
+    /// to stream events back, rather than sending a single response.
+    pub fn is_stream(&self) -> bool {
+        matches!(
+            self,
+            Self::Subscribe { .. } | Self::Var(IronvarCommand::Watch { .. })
+        )
+    }
 }
 
 #[derive(Subcommand, Debug, Serialize, Deserialize)]
@@ -40,16 +112,55 @@ pub enum IronvarCommand {
         key: Box<str>,
         /// Variable value. Can be any valid UTF-8 string.
         value: String,
+        /// Parse the value as JSON,
+        /// allowing its fields to be accessed using `key.path.to.field`.
+        #[arg(long)]
+        #[serde(default)]
+        json: bool,
     },
 
     /// Get the current value of an `ironvar`.
     Get {
+        /// Variable key.
+        /// Use `key.path.to.field` to get a field from a JSON value.
+        key: Box<str>,
+    },
+
+    /// Remove the value of an `ironvar`.
+    /// Any references to this variable are cleared.
+    Unset {
         /// Variable key.
         key: Box<str>,
     },
 
     /// Gets the current value of all `ironvar`s.
     List { namespace: Option<Box<str>> },
+
+    /// List the available read-only namespaces.
+    Namespaces {
+        /// List the namespaces inside this namespace instead.
+        /// Use `.` to separate nested namespaces.
+        namespace: Option<Box<str>>,
+    },
+
+    /// Watch an `ironvar`, printing its value each time it changes.
+    /// The connection is kept open, and each value is written as an event.
+    Watch {
+        /// Variable key.
+        /// Use `key.path.to.field` to watch a field from a JSON value.
+        key: Box<str>,
+    },
+}
+
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+#[serde(tag = "subcommand", rename_all = "snake_case")]
+pub enum ScriptCommand {
+    /// Run the script for all `script` modules with the given name again immediately.
+    /// Watch scripts are restarted.
+    Refresh {
+        /// The configured name of the module.
+        name: String,
+    },
 }
 
 #[derive(Args, Debug, Serialize, Deserialize)]
@@ -126,6 +237,42 @@ pub enum BarCommandType {
         )]
         exclusive: bool,
     },
+
+    // == Modules == \\
+    /// Add a module to the bar.
+    AddModule {
+        /// The container to add the module to.
+        #[arg(value_enum)]
+        location: ModuleLocation,
+        /// The module config, as a JSON object.
+        #[arg(value_parser = parse_json)]
+        config: serde_json::Value,
+        /// The position within the container to insert the module at.
+        /// Defaults to the end of the container.
+        #[arg(long)]
+        index: Option<usize>,
+    },
+    /// Remove all modules with the given name from the bar.
+    RemoveModule {
+        /// The configured name of the widget.
+        widget_name: String,
+    },
+    /// Move a module to a new position.
+    MoveModule {
+        /// The configured name of the widget.
+        widget_name: String,
+        /// The container to move the module to.
+        #[arg(value_enum)]
+        location: ModuleLocation,
+        /// The position within the container to move the module to.
+        /// Defaults to the end of the container.
+        #[arg(long)]
+        index: Option<usize>,
+    },
+}
+
+fn parse_json(value: &str) -> serde_json::Result<serde_json::Value> {
+    serde_json::from_str(value)
 }
 
 #[derive(Subcommand, Debug, Serialize, Deserialize)]
@@ -0,0 +1,75 @@
+use crate::Ironbar;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// An event streamed to clients subscribed using [`Command::Subscribe`](super::Command::Subscribe).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// An `ironvar` value was set.
+    Var {
+        key: Box<str>,
+        value: Option<String>,
+    },
+    /// A bar was shown or hidden.
+    BarVisibility { name: String, visible: bool },
+    /// A popup was opened or closed.
+    Popup {
+        bar_name: String,
+        widget_name: Option<String>,
+        visible: bool,
+    },
+    /// The config was reloaded.
+    Reload,
+}
+
+/// The types of event which can be subscribed to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ValueEnum, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    Var,
+    BarVisibility,
+    Popup,
+    Reload,
+}
+
+impl Event {
+    pub const fn event_type(&self) -> EventType {
+        match self {
+            Self::Var { .. } => EventType::Var,
+            Self::BarVisibility { .. } => EventType::BarVisibility,
+            Self::Popup { .. } => EventType::Popup,
+            Self::Reload => EventType::Reload,
+        }
+    }
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Var { key, value } => {
+                write!(f, "var {key}: {}", value.as_deref().unwrap_or_default())
+            }
+            Self::BarVisibility { name, visible } => write!(f, "bar_visibility {name}: {visible}"),
+            Self::Popup {
+                bar_name,
+                widget_name,
+                visible,
+            } => write!(
+                f,
+                "popup {bar_name}/{}: {visible}",
+                widget_name.as_deref().unwrap_or_default()
+            ),
+            Self::Reload => write!(f, "reload"),
+        }
+    }
+}
+
+/// Broadcasts an event to all subscribed IPC clients.
+///
+/// This is a no-op if there are no subscribers.
+pub fn emit(event: Event) {
+    // sending only fails if there are no receivers, which is expected
+    let _ = Ironbar::ipc_events().send(event);
+}
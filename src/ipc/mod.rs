@@ -1,26 +1,46 @@
 mod client;
 pub mod commands;
+pub mod events;
 pub mod responses;
 mod server;
 
+use serde::Serialize;
+use std::fs;
+use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use tracing::warn;
 
 pub use commands::*;
+pub use events::{Event, EventType};
 pub use responses::Response;
 
+const SOCKET_PREFIX: &str = "ironbar-ipc";
+const SOCKET_EXTENSION: &str = ".sock";
+
+pub use crate::DEFAULT_INSTANCE;
+
 #[derive(Debug)]
 pub struct Ipc {
     path: PathBuf,
 }
 
+/// A running Ironbar instance.
+#[derive(Debug, Serialize)]
+pub struct Instance {
+    pub name: String,
+    pub path: PathBuf,
+}
+
 impl Ipc {
     /// Creates a new IPC instance.
     /// This can be used as both a server and client.
-    pub fn new() -> Self {
-        let ipc_socket_file = std::env::var("XDG_RUNTIME_DIR")
-            .map_or_else(|_| PathBuf::from("/tmp"), PathBuf::from)
-            .join("ironbar-ipc.sock");
+    ///
+    /// Each named `instance` gets its own socket.
+    pub fn new(instance: Option<&str>) -> Self {
+        let ipc_socket_file = runtime_dir().join(match instance {
+            None | Some(DEFAULT_INSTANCE) => format!("{SOCKET_PREFIX}{SOCKET_EXTENSION}"),
+            Some(instance) => format!("{SOCKET_PREFIX}-{instance}{SOCKET_EXTENSION}"),
+        });
 
         if format!("{}", ipc_socket_file.display()).len() > 100 {
             warn!(
@@ -36,4 +56,49 @@ impl Ipc {
     pub fn path(&self) -> &Path {
         self.path.as_path()
     }
+
+    /// Checks whether a server is accepting connections on the socket.
+    pub fn is_running(&self) -> bool {
+        UnixStream::connect(&self.path).is_ok()
+    }
+
+    /// Gets all running instances, sorted by name.
+    ///
+    /// Stale sockets left behind by instances
+    /// which did not shut down cleanly are ignored.
+    pub fn instances() -> Vec<Instance> {
+        let Ok(entries) = fs::read_dir(runtime_dir()) else {
+            return vec![];
+        };
+
+        let mut instances = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                let name = file_name
+                    .to_str()?
+                    .strip_prefix(SOCKET_PREFIX)?
+                    .strip_suffix(SOCKET_EXTENSION)?;
+
+                let name = match name {
+                    "" => DEFAULT_INSTANCE,
+                    name => name.strip_prefix('-')?,
+                };
+
+                Some(Instance {
+                    name: name.to_string(),
+                    path: entry.path(),
+                })
+            })
+            .filter(|instance| UnixStream::connect(&instance.path).is_ok())
+            .collect::<Vec<_>>();
+
+        instances.sort_by(|a, b| a.name.cmp(&b.name));
+        instances
+    }
+}
+
+/// Gets the directory the IPC sockets are created in.
+fn runtime_dir() -> PathBuf {
+    std::env::var("XDG_RUNTIME_DIR").map_or_else(|_| PathBuf::from("/tmp"), PathBuf::from)
 }
@@ -4,12 +4,45 @@ use serde::{Deserialize, Serialize};
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Response {
     Ok,
-    OkValue { value: String },
-    Multi { values: Vec<String> },
-    Err { message: Option<String> },
+    OkValue {
+        value: String,
+    },
+    Multi {
+        values: Vec<String>,
+    },
+    /// A structured value.
+    /// Lists of objects are printed as a table in plain mode.
+    Json {
+        value: serde_json::Value,
+    },
+    /// The responses to each command in a batch, in order.
+    Batch {
+        responses: Vec<Response>,
+    },
+    Err {
+        message: Option<String>,
+    },
 }
 
 impl Response {
+    /// Creates a new `Response::Json` by serializing `value`.
+    pub fn json<T: Serialize>(value: &T) -> Self {
+        match serde_json::to_value(value) {
+            Ok(value) => Self::Json { value },
+            Err(err) => Self::error(&err.to_string()),
+        }
+    }
+
+    /// Gets whether this is an error response,
+    /// or a batch containing an error response.
+    pub fn is_err(&self) -> bool {
+        match self {
+            Self::Err { .. } => true,
+            Self::Batch { responses } => responses.iter().any(Self::is_err),
+            _ => false,
+        }
+    }
+
     /// Creates a new `Response::Error`.
     pub fn error(message: &str) -> Self {
         Self::Err {
@@ -1,7 +1,10 @@
 use super::Response;
 use crate::Ironbar;
 use crate::bar::Bar;
+use crate::config::ModuleConfig;
 use crate::ipc::{BarCommand, BarCommandType};
+use crate::modules::ModuleLocation;
+use serde::Deserialize;
 use std::rc::Rc;
 
 pub fn handle_command(command: &BarCommand, ironbar: &Rc<Ironbar>) -> Response {
@@ -46,6 +49,27 @@ pub fn handle_command(command: &BarCommand, ironbar: &Rc<Ironbar>) -> Response {
                 bar.set_exclusive(*exclusive);
                 Response::Ok
             }
+            AddModule {
+                location,
+                config,
+                index,
+            } => add_module(&bar, config, location, *index),
+            RemoveModule { widget_name } => {
+                if bar.remove_module(widget_name) {
+                    Response::Ok
+                } else {
+                    Response::error("Invalid module name")
+                }
+            }
+            MoveModule {
+                widget_name,
+                location,
+                index,
+            } => match bar.move_module(widget_name, location.clone(), *index) {
+                Ok(true) => Response::Ok,
+                Ok(false) => Response::error("Invalid module name"),
+                Err(err) => Response::error(&format!("Failed to create module: {err}")),
+            },
         })
         .reduce(|acc, rsp| match (acc, rsp) {
             // If all responses are `Ok`, return one `Ok`. We assume we'll never mix `Ok` and `OkValue`.
@@ -63,6 +87,44 @@ pub fn handle_command(command: &BarCommand, ironbar: &Rc<Ironbar>) -> Response {
         .unwrap_or(Response::error("Invalid bar name"))
 }
 
+/// Checks that `command` can run, without running it.
+pub fn check_command(command: &BarCommand, ironbar: &Rc<Ironbar>) -> Result<(), String> {
+    use BarCommandType::*;
+
+    let bars = ironbar.bars_by_name(&command.name);
+    if bars.is_empty() {
+        return Err("Invalid bar name".to_string());
+    }
+
+    bars.iter().try_for_each(|bar| match &command.subcommand {
+        ShowPopup { widget_name }
+        | SetPopupVisible {
+            widget_name,
+            visible: true,
+        } => check_popup(bar, widget_name),
+        TogglePopup { widget_name } if !bar.popup().visible() => check_popup(bar, widget_name),
+        AddModule { config, .. } => ModuleConfig::deserialize(config)
+            .map(|_| ())
+            .map_err(|err| format!("Invalid module config: {err}")),
+        RemoveModule { widget_name } | MoveModule { widget_name, .. } => {
+            if bar.modules().iter().any(|m| &m.name == widget_name) {
+                Ok(())
+            } else {
+                Err("Invalid module name".to_string())
+            }
+        }
+        _ => Ok(()),
+    })
+}
+
+fn check_popup(bar: &Bar, widget_name: &str) -> Result<(), String> {
+    match bar.modules().into_iter().find(|m| m.name == widget_name) {
+        Some(module_ref) if module_ref.popup.is_some() => Ok(()),
+        Some(_) => Err("Module has no popup functionality".to_string()),
+        None => Err("Invalid module name".to_string()),
+    }
+}
+
 fn set_visible(bar: &Bar, visible: bool) -> Response {
     bar.set_visible(visible);
     Response::Ok
@@ -74,9 +136,10 @@ fn show_popup(bar: &Bar, widget_name: &str) -> Response {
     // only one popup per bar, so hide if open for another widget
     popup.hide();
 
-    let module_ref = bar.modules().iter().find(|m| m.name == widget_name);
+    let module_ref = bar.modules().into_iter().find(|m| m.name == widget_name);
 
     let module_button = module_ref
+        .as_ref()
         .and_then(|m| m.popup.clone())
         .and_then(|popup| popup.buttons.first().cloned());
 
@@ -93,6 +156,23 @@ fn show_popup(bar: &Bar, widget_name: &str) -> Response {
     }
 }
 
+fn add_module(
+    bar: &Bar,
+    config: &serde_json::Value,
+    location: &ModuleLocation,
+    index: Option<usize>,
+) -> Response {
+    let config = match ModuleConfig::deserialize(config) {
+        Ok(config) => config,
+        Err(err) => return Response::error(&format!("Invalid module config: {err}")),
+    };
+
+    match bar.add_module(config, location.clone(), index) {
+        Ok(()) => Response::Ok,
+        Err(err) => Response::error(&format!("Failed to create module: {err}")),
+    }
+}
+
 fn hide_popup(bar: &Bar) -> Response {
     let popup = bar.popup();
     popup.hide();
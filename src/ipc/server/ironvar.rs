@@ -1,73 +1,211 @@
 use crate::Ironbar;
-use crate::ipc::{IronvarCommand, Response};
-use crate::ironvar::{Namespace, WritableNamespace};
-use std::sync::Arc;
+use crate::ipc::{Event, IronvarCommand, Response};
+use crate::ironvar::{Namespace, NamespaceTrait, VariablePath, WritableNamespace, value_to_string};
+use color_eyre::Result;
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::select;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, trace};
+
+#[derive(Debug, Serialize)]
+struct ListEntry {
+    key: String,
+    #[serde(rename = "type")]
+    kind: EntryKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum EntryKind {
+    Namespace,
+    Variable,
+}
 
 pub fn handle_command(command: IronvarCommand) -> Response {
     match command {
-        IronvarCommand::Set { key, value } => {
+        IronvarCommand::Set { key, value, json } => {
             let variable_manager = Ironbar::variable_manager();
-            match variable_manager.set(&key, value) {
+
+            let res = if json {
+                match serde_json::from_str(&value) {
+                    Ok(value) => variable_manager.set_value(&key, value),
+                    Err(err) => return Response::error(&format!("Invalid JSON: {err}")),
+                }
+            } else {
+                variable_manager.set(&key, value)
+            };
+
+            match res {
                 Ok(()) => Response::Ok,
                 Err(err) => Response::error(&format!("{err}")),
             }
         }
-        IronvarCommand::Get { mut key } => {
+        IronvarCommand::Get { key } => {
             let variable_manager = Ironbar::variable_manager();
-            let mut ns: Arc<dyn Namespace + Sync + Send> = variable_manager;
-
-            if key.contains('.') {
-                for part in key.split('.') {
-                    ns = if let Some(ns) = ns.get_namespace(part) {
-                        ns.clone()
-                    } else {
-                        key = part.into();
-                        break;
-                    };
-                }
-            }
-
-            let value = ns.get(&key);
-            match value {
-                Some(value) => Response::OkValue { value },
+            match variable_manager.get_value(&key) {
+                Some(value) => Response::Json { value },
                 None => Response::error("Variable not found"),
             }
         }
-        IronvarCommand::List { namespace } => {
+        IronvarCommand::Unset { key } => {
             let variable_manager = Ironbar::variable_manager();
-            let mut ns: Arc<dyn Namespace + Sync + Send> = variable_manager;
-
-            if let Some(namespace) = namespace {
-                for part in namespace.split('.') {
-                    ns = match ns.get_namespace(part) {
-                        Some(ns) => ns.clone(),
-                        None => return Response::error("Namespace not found"),
-                    };
-                }
+            match variable_manager.remove(&key) {
+                Ok(()) => Response::Ok,
+                Err(err) => Response::error(&format!("{err}")),
             }
+        }
+        IronvarCommand::List { namespace } => {
+            let Some(ns) = find_namespace(namespace.as_deref()) else {
+                return Response::error("Namespace not found");
+            };
 
             let mut namespaces = ns
                 .namespaces()
                 .iter()
-                .map(|ns| format!("<{ns}>"))
+                .map(|ns| ListEntry {
+                    key: ns.clone(),
+                    kind: EntryKind::Namespace,
+                    value: None,
+                })
                 .collect::<Vec<_>>();
 
-            namespaces.sort();
-
-            let mut value = namespaces.join("\n");
+            namespaces.sort_by(|a, b| a.key.cmp(&b.key));
 
             let mut values = ns
                 .get_all()
-                .iter()
-                .map(|(k, v)| format!("{k}: {v}"))
+                .into_iter()
+                .map(|(key, value)| ListEntry {
+                    key: key.to_string(),
+                    kind: EntryKind::Variable,
+                    value: Some(value),
+                })
                 .collect::<Vec<_>>();
 
-            values.sort();
+            values.sort_by(|a, b| a.key.cmp(&b.key));
+
+            namespaces.append(&mut values);
+            Response::json(&namespaces)
+        }
+        IronvarCommand::Namespaces { namespace } => {
+            let Some(ns) = find_namespace(namespace.as_deref()) else {
+                return Response::error("Namespace not found");
+            };
+
+            let mut namespaces = ns.namespaces();
+            namespaces.sort();
+
+            Response::Multi { values: namespaces }
+        }
+        // handled in `handle_watch`
+        IronvarCommand::Watch { .. } => Response::error("Watches cannot be run here"),
+    }
+}
+
+/// Checks that `command` can run, without running it.
+pub fn check_command(command: &IronvarCommand) -> Result<(), String> {
+    let variable_manager = Ironbar::variable_manager();
+
+    match command {
+        IronvarCommand::Set { key, value, json } => {
+            if *json {
+                serde_json::from_str::<serde_json::Value>(value)
+                    .map_err(|err| format!("Invalid JSON: {err}"))?;
+            }
+
+            variable_manager
+                .check_set(key)
+                .map_err(|err| err.to_string())
+        }
+        IronvarCommand::Get { key } => match variable_manager.get_value(key) {
+            Some(_) => Ok(()),
+            None => Err("Variable not found".to_string()),
+        },
+        IronvarCommand::Unset { key } => variable_manager
+            .check_remove(key)
+            .map_err(|err| err.to_string()),
+        IronvarCommand::List { namespace } | IronvarCommand::Namespaces { namespace } => {
+            match find_namespace(namespace.as_deref()) {
+                Some(_) => Ok(()),
+                None => Err("Namespace not found".to_string()),
+            }
+        }
+        IronvarCommand::Watch { .. } => Err("Watches cannot be run here".to_string()),
+    }
+}
+
+/// Gets the namespace at `path`,
+/// using `.` to separate nested namespaces.
+///
+/// If no path is given, the root namespace is returned.
+fn find_namespace(path: Option<&str>) -> Option<NamespaceTrait> {
+    let mut ns: NamespaceTrait = Ironbar::variable_manager();
+
+    if let Some(path) = path {
+        for part in path.split('.') {
+            ns = ns.get_namespace(part)?;
+        }
+    }
+
+    Some(ns)
+}
+
+/// Takes an incoming watch connection,
+/// and writes the value of `key` to it as a JSON event line
+/// each time it changes.
+///
+/// The current value is written immediately.
+/// The connection is held open until the client disconnects.
+pub async fn handle_watch(stream: UnixStream, key: Box<str>) -> Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+
+    let path = VariablePath::parse(&key);
+    let mut rx = Ironbar::variable_manager().subscribe(path.key.clone());
+
+    let mut res = serde_json::to_vec(&Response::Ok)?;
+    res.push(b'\n');
+    writer.write_all(&res).await?;
 
-            value.push('\n');
-            value.push_str(&values.join("\n"));
+    let mut read_buffer = [0; 64];
+    let mut last = None;
 
-            Response::OkValue { value }
+    loop {
+        let value = select! {
+            value = rx.recv() => match value {
+                Ok(value) => value
+                    .as_ref()
+                    .and_then(|value| path.resolve(value))
+                    .map(value_to_string),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            },
+            // watching clients are not expected to write,
+            // so any read completing indicates the client has gone away.
+            _ = reader.read(&mut read_buffer) => break,
+        };
+
+        if last.as_ref() == Some(&value) {
+            continue;
+        }
+
+        let event = Event::Var {
+            key: key.clone(),
+            value: value.clone(),
+        };
+        last = Some(value);
+
+        let mut res = serde_json::to_vec(&event)?;
+        res.push(b'\n');
+
+        trace!("writing event: {event:?}");
+        if writer.write_all(&res).await.is_err() {
+            break;
         }
     }
+
+    debug!("IPC watcher disconnected");
+    Ok(())
 }
@@ -1,34 +1,59 @@
 mod bar;
 mod ironvar;
+mod module;
+mod script;
 mod style;
+mod tree;
 
 use std::fs;
 use std::path::Path;
 use std::rc::Rc;
+use std::time::Duration;
 
-use color_eyre::{Report, Result};
+use color_eyre::{Help, Report, Result};
 use gtk::Application;
-use gtk::prelude::*;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{UnixListener, UnixStream};
-use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::select;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{self, Sender};
+use tokio::sync::oneshot;
+use tokio::time::timeout;
 use tracing::{debug, error, info, trace, warn};
 
 use super::Ipc;
-use crate::channels::{AsyncSenderExt, MpscReceiverExt};
-use crate::ipc::{Command, Response};
+use crate::channels::MpscReceiverExt;
+use crate::ipc::{Command, Event, EventType, IronvarCommand, Response};
 use crate::{Ironbar, spawn};
 
+/// Maximum time to wait for a client to send its command
+/// before closing the connection.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A command received from a client,
+/// along with the channel to send its response on.
+type Request = (Command, oneshot::Sender<Response>);
+
 impl Ipc {
     /// Starts the IPC server on its socket.
     ///
     /// Once started, the server will begin accepting connections.
-    pub fn start(&self, application: &Application, ironbar: Rc<Ironbar>) {
-        let (cmd_tx, cmd_rx) = mpsc::channel(32);
-        let (res_tx, mut res_rx) = mpsc::channel(32);
+    ///
+    /// Returns an error without starting
+    /// if another instance is already using the socket.
+    pub fn start(&self, application: &Application, ironbar: Rc<Ironbar>) -> Result<()> {
+        let (cmd_tx, cmd_rx) = mpsc::channel::<Request>(32);
 
         let path = self.path.clone();
 
+        if self.is_running() {
+            return Err(Report::msg(format!(
+                "Another Ironbar instance is already listening on {}",
+                path.display()
+            ))
+            .suggestion("Use `--instance <name>` to run multiple instances side by side"));
+        }
+
         if path.exists() {
             warn!("Socket already exists. Did Ironbar exit abruptly?");
             warn!("Attempting IPC shutdown to allow binding to address");
@@ -53,12 +78,16 @@ impl Ipc {
                 match listener.accept().await {
                     Ok((stream, _addr)) => {
                         debug!("handling incoming connection");
-                        if let Err(err) =
-                            Self::handle_connection(stream, &cmd_tx, &mut res_rx).await
-                        {
-                            error!("{err:?}");
-                        }
-                        debug!("done");
+
+                        // each connection is handled in its own task
+                        // so that a slow client cannot block others.
+                        let cmd_tx = cmd_tx.clone();
+                        spawn(async move {
+                            if let Err(err) = Self::handle_connection(stream, &cmd_tx).await {
+                                error!("{err:?}");
+                            }
+                            debug!("done");
+                        });
                     }
                     Err(err) => {
                         error!("{err:?}");
@@ -67,48 +96,93 @@ impl Ipc {
             }
         });
 
-        cmd_rx.recv_glib(application, move |application, command| {
+        cmd_rx.recv_glib(application, move |application, (command, res_tx)| {
             let res = Self::handle_command(command, application, &ironbar);
-            res_tx.send_spawn(res);
+
+            // the client may have disconnected in the meantime,
+            // in which case there is nobody to respond to.
+            if res_tx.send(res).is_err() {
+                debug!("IPC client disconnected before response was sent");
+            }
         });
+
+        Ok(())
     }
 
     /// Takes an incoming connections,
     /// reads the command message, and sends the response.
     ///
     /// The connection is closed once the response has been written.
-    async fn handle_connection(
-        mut stream: UnixStream,
-        cmd_tx: &Sender<Command>,
-        res_rx: &mut Receiver<Response>,
-    ) -> Result<()> {
-        trace!("awaiting readable state");
-        stream.readable().await?;
-
+    async fn handle_connection(mut stream: UnixStream, cmd_tx: &Sender<Request>) -> Result<()> {
         let mut read_buffer = Vec::with_capacity(1024);
 
         let mut reader = BufReader::new(&mut stream);
 
         trace!("reading bytes");
-        let bytes = reader.read_until(b'\n', &mut read_buffer).await?;
+        let Ok(bytes) = timeout(READ_TIMEOUT, reader.read_until(b'\n', &mut read_buffer)).await
+        else {
+            warn!("IPC client did not send a command within {READ_TIMEOUT:?}");
+            return Self::write_response(&mut stream, &Response::error("Timed out")).await;
+        };
+
+        let bytes = bytes?;
         debug!("read {} bytes", bytes);
 
-        // FIXME: Error on invalid command
-        let command = serde_json::from_slice::<Command>(&read_buffer[..bytes])?;
+        // clients checking whether the server is running
+        // connect and immediately disconnect.
+        if bytes == 0 {
+            return Ok(());
+        }
+
+        let command = match serde_json::from_slice::<Command>(&read_buffer[..bytes]) {
+            Ok(command) => command,
+            Err(err) => {
+                warn!("Received invalid IPC command: {err}");
+                let res = Response::error(&format!("Invalid command: {err}"));
+                return Self::write_response(&mut stream, &res).await;
+            }
+        };
 
         debug!("Received command: {command:?}");
 
-        cmd_tx.send_expect(command).await;
-        let res = res_rx
-            .recv()
-            .await
-            .unwrap_or(Response::Err { message: None });
+        // subscriptions and watches hold the connection open,
+        // so must be handled separately to avoid blocking other clients.
+        if command.is_stream() {
+            spawn(async move {
+                let res = match command {
+                    Command::Subscribe { events } => {
+                        Self::handle_subscription(stream, events).await
+                    }
+                    Command::Var(IronvarCommand::Watch { key }) => {
+                        ironvar::handle_watch(stream, key).await
+                    }
+                    _ => unreachable!(),
+                };
 
-        let mut res = serde_json::to_vec(&res)?;
-        res.push(b'\n');
+                if let Err(err) = res {
+                    error!("{err:?}");
+                }
+            });
+
+            return Ok(());
+        }
 
-        trace!("awaiting writable state");
-        stream.writable().await?;
+        let (res_tx, res_rx) = oneshot::channel();
+
+        let res = if cmd_tx.send((command, res_tx)).await.is_ok() {
+            res_rx.await.unwrap_or(Response::Err { message: None })
+        } else {
+            Response::Err { message: None }
+        };
+
+        Self::write_response(&mut stream, &res).await
+    }
+
+    /// Writes a response to the stream as a JSON line,
+    /// then shuts the stream down.
+    async fn write_response(stream: &mut UnixStream, res: &Response) -> Result<()> {
+        let mut res = serde_json::to_vec(res)?;
+        res.push(b'\n');
 
         debug!("writing {} bytes", res.len());
         stream.write_all(&res).await?;
@@ -119,6 +193,62 @@ impl Ipc {
         Ok(())
     }
 
+    /// Takes an incoming subscription connection,
+    /// and writes each matching event to it as a JSON line.
+    ///
+    /// The connection is held open until the client disconnects.
+    async fn handle_subscription(stream: UnixStream, event_types: Vec<EventType>) -> Result<()> {
+        let (mut reader, mut writer) = stream.into_split();
+
+        let mut vars_rx = Ironbar::variable_manager().subscribe_all();
+        let mut events_rx = Ironbar::ipc_events().subscribe();
+
+        let mut res = serde_json::to_vec(&Response::Ok)?;
+        res.push(b'\n');
+        writer.write_all(&res).await?;
+
+        let mut read_buffer = [0; 64];
+
+        loop {
+            let event = select! {
+                change = vars_rx.recv() => match change {
+                    Ok(change) => Event::Var { key: change.key, value: change.value },
+                    Err(RecvError::Lagged(count)) => {
+                        warn!("IPC subscription lagged behind by {count} ironvar changes");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                event = events_rx.recv() => match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(count)) => {
+                        warn!("IPC subscription lagged behind by {count} events");
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                // subscribed clients are not expected to write,
+                // so any read completing indicates the client has gone away.
+                _ = reader.read(&mut read_buffer) => break,
+            };
+
+            if !event_types.is_empty() && !event_types.contains(&event.event_type()) {
+                continue;
+            }
+
+            let mut res = serde_json::to_vec(&event)?;
+            res.push(b'\n');
+
+            trace!("writing event: {event:?}");
+            if writer.write_all(&res).await.is_err() {
+                break;
+            }
+        }
+
+        debug!("IPC subscriber disconnected");
+        Ok(())
+    }
+
     /// Takes an input command, runs it and returns with the appropriate response.
     ///
     /// This runs on the main thread, allowing commands to interact with GTK.
@@ -133,26 +263,109 @@ impl Ipc {
                 gtk::Window::set_interactive_debugging(true);
                 Response::Ok
             }
-            Command::Reload => {
-                info!("Closing existing bars");
-                ironbar.bars.borrow_mut().clear();
+            Command::Reload => match ironbar.reload(application) {
+                Ok(()) => Response::Ok,
+                Err(err) => {
+                    error!("Failed to reload config: {err}");
+                    Response::error(&err.to_string())
+                }
+            },
+            Command::Var(cmd) => ironvar::handle_command(cmd),
+            Command::Bar(cmd) => bar::handle_command(&cmd, ironbar),
+            Command::Style(cmd) => style::handle_command(cmd, ironbar),
+            Command::Script(cmd) => script::handle_command(cmd, ironbar),
+            Command::Module { name, action, args } => {
+                module::handle_command(&name, &action, &args, ironbar)
+            }
+            Command::Tree => tree::handle_command(ironbar),
+            Command::Instances => Response::json(&Self::instances()),
+            #[cfg(feature = "config")]
+            Command::Check { path } => {
+                let location = path.map_or_else(
+                    || ironbar.config_location.clone(),
+                    crate::config::ConfigLocation::Custom,
+                );
 
-                let windows = application.windows();
-                for window in windows {
-                    window.close();
+                let report = crate::config::validate::validate(&location);
+                if report.is_valid() {
+                    Response::Ok
+                } else {
+                    Response::error(&report.to_string())
                 }
+            }
+            #[cfg(not(feature = "config"))]
+            Command::Check { .. } => Response::error("Ironbar was built without config support"),
+            Command::Batch { commands } => {
+                // check everything up front, so an invalid batch changes nothing
+                let invalid = commands.iter().enumerate().find_map(|(i, command)| {
+                    Self::check_command(command, ironbar)
+                        .err()
+                        .map(|err| (i, err))
+                });
 
-                ironbar.reload_config();
+                if let Some((i, err)) = invalid {
+                    return Response::error(&format!(
+                        "Command {} is invalid, so no commands were run: {err}",
+                        i + 1
+                    ));
+                }
 
-                match crate::load_output_bars(ironbar, application) {
-                    Ok(_) => {}
-                    Err(err) => error!("{err:?}"),
+                let mut responses = vec![];
+
+                for command in commands {
+                    let response = Self::handle_command(command, application, ironbar);
+                    let failed = response.is_err();
+                    responses.push(response);
+
+                    // later commands may depend on earlier ones succeeding
+                    if failed {
+                        break;
+                    }
                 }
-                Response::Ok
+
+                Response::Batch { responses }
             }
-            Command::Var(cmd) => ironvar::handle_command(cmd),
-            Command::Bar(cmd) => bar::handle_command(&cmd, ironbar),
-            Command::Style(cmd) => style::handle_command(cmd, ironbar),
+            // handled in `handle_connection`
+            Command::Subscribe { .. } => Response::error("Subscriptions cannot be run here"),
+        }
+    }
+
+    /// Checks that a command can run, without running it.
+    ///
+    /// Commands are checked against the current state,
+    /// so this cannot account for changes made by earlier commands in a batch.
+    fn check_command(command: &Command, ironbar: &Rc<Ironbar>) -> Result<(), String> {
+        match command {
+            Command::Ping | Command::Inspect | Command::Tree | Command::Instances => Ok(()),
+            Command::Reload => crate::config::Config::check(&ironbar.config_location)
+                .map_err(|err| err.to_string()),
+            Command::Var(cmd) => ironvar::check_command(cmd),
+            Command::Bar(cmd) => bar::check_command(cmd, ironbar),
+            Command::Style(cmd) => style::check_command(cmd, ironbar),
+            Command::Script(cmd) => script::check_command(cmd, ironbar),
+            Command::Module { name, action, args } => {
+                module::check_command(name, action, args, ironbar)
+            }
+            #[cfg(feature = "config")]
+            Command::Check { path } => {
+                let location = path.clone().map_or_else(
+                    || ironbar.config_location.clone(),
+                    crate::config::ConfigLocation::Custom,
+                );
+
+                let report = crate::config::validate::validate(&location);
+                if report.is_valid() {
+                    Ok(())
+                } else {
+                    Err(report.to_string())
+                }
+            }
+            #[cfg(not(feature = "config"))]
+            Command::Check { .. } => Err("Ironbar was built without config support".to_string()),
+            Command::Batch { commands } => commands
+                .iter()
+                .try_for_each(|command| Self::check_command(command, ironbar)),
+            Command::Subscribe { .. } => Err("Subscriptions cannot be run here".to_string()),
         }
     }
 
@@ -0,0 +1,48 @@
+use crate::Ironbar;
+use crate::ipc::Response;
+use crate::modules::ModuleRef;
+
+pub fn handle_command(name: &str, action: &str, args: &[String], ironbar: &Ironbar) -> Response {
+    let modules = modules_by_name(name, ironbar);
+
+    if modules.is_empty() {
+        return Response::error("Module not found");
+    }
+
+    for module in modules {
+        if let Err(err) = module.actions.send(action, args) {
+            return Response::error(&err.to_string());
+        }
+    }
+
+    Response::Ok
+}
+
+/// Checks that `action` can be sent to the modules named `name`, without sending it.
+pub fn check_command(
+    name: &str,
+    action: &str,
+    args: &[String],
+    ironbar: &Ironbar,
+) -> Result<(), String> {
+    let modules = modules_by_name(name, ironbar);
+
+    if modules.is_empty() {
+        return Err("Module not found".to_string());
+    }
+
+    modules
+        .iter()
+        .try_for_each(|module| module.actions.check(action, args))
+        .map_err(|err| err.to_string())
+}
+
+fn modules_by_name(name: &str, ironbar: &Ironbar) -> Vec<ModuleRef> {
+    ironbar
+        .bars
+        .borrow()
+        .iter()
+        .flat_map(|bar| bar.modules())
+        .filter(|module| module.name == name)
+        .collect()
+}
@@ -0,0 +1,51 @@
+use crate::Ironbar;
+use crate::ipc::{Response, ScriptCommand};
+use crate::modules::ModuleRef;
+
+pub fn handle_command(command: ScriptCommand, ironbar: &Ironbar) -> Response {
+    match command {
+        ScriptCommand::Refresh { name } => {
+            let modules = script_modules(&name, ironbar);
+
+            if modules.is_empty() {
+                return Response::error("Script module not found");
+            }
+
+            for module in modules {
+                if let Err(err) = module.actions.send("refresh", &[]) {
+                    return Response::error(&err.to_string());
+                }
+            }
+
+            Response::Ok
+        }
+    }
+}
+
+/// Checks that `command` can run, without running it.
+pub fn check_command(command: &ScriptCommand, ironbar: &Ironbar) -> Result<(), String> {
+    match command {
+        ScriptCommand::Refresh { name } => {
+            let modules = script_modules(name, ironbar);
+
+            if modules.is_empty() {
+                return Err("Script module not found".to_string());
+            }
+
+            modules
+                .iter()
+                .try_for_each(|module| module.actions.check("refresh", &[]))
+                .map_err(|err| err.to_string())
+        }
+    }
+}
+
+fn script_modules(name: &str, ironbar: &Ironbar) -> Vec<ModuleRef> {
+    ironbar
+        .bars
+        .borrow()
+        .iter()
+        .flat_map(|bar| bar.modules())
+        .filter(|module| module.module_type == "script" && module.name == name)
+        .collect()
+}
@@ -64,7 +64,29 @@ pub fn handle_command(command: StyleCommand, ironbar: &Ironbar) -> Response {
     }
 }
 
-fn modules_by_name<'a>(bars: &'a [Bar], name: &str) -> Vec<&'a ModuleRef> {
+/// Checks that `command` can run, without running it.
+pub fn check_command(command: &StyleCommand, ironbar: &Ironbar) -> Result<(), String> {
+    match command {
+        StyleCommand::LoadCss { path } => {
+            if path.exists() {
+                Ok(())
+            } else {
+                Err("File not found".to_string())
+            }
+        }
+        StyleCommand::AddClass { module_name, .. }
+        | StyleCommand::RemoveClass { module_name, .. }
+        | StyleCommand::ToggleClass { module_name, .. } => {
+            if modules_by_name(&ironbar.bars.borrow(), module_name).is_empty() {
+                Err("Module not found".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+fn modules_by_name(bars: &[Bar], name: &str) -> Vec<ModuleRef> {
     bars.iter()
         .flat_map(Bar::modules)
         .filter(|w| w.name == name)
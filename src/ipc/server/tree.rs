@@ -0,0 +1,77 @@
+use crate::Ironbar;
+use crate::bar::Bar;
+use crate::config::BarPosition;
+use crate::ipc::Response;
+use crate::modules::{ModuleLocation, ModuleRef};
+use gtk::prelude::*;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct BarNode {
+    name: String,
+    monitor: String,
+    position: BarPosition,
+    visible: bool,
+    exclusive_zone: i32,
+    modules: Vec<ModuleNode>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModuleNode {
+    id: usize,
+    #[serde(rename = "type")]
+    module_type: &'static str,
+    name: String,
+    classes: Vec<String>,
+    location: ModuleLocation,
+    popup_open: bool,
+}
+
+pub fn handle_command(ironbar: &Ironbar) -> Response {
+    let bars = ironbar
+        .bars
+        .borrow()
+        .iter()
+        .map(BarNode::from)
+        .collect::<Vec<_>>();
+
+    Response::json(&bars)
+}
+
+impl From<&Bar> for BarNode {
+    fn from(bar: &Bar) -> Self {
+        let popup = bar.popup();
+        let open_popup = popup.current_widget().filter(|_| popup.visible());
+
+        Self {
+            name: bar.name().to_string(),
+            monitor: bar.monitor_name().to_string(),
+            position: bar.position(),
+            visible: bar.visible(),
+            exclusive_zone: bar.exclusive_zone(),
+            modules: bar
+                .modules()
+                .iter()
+                .map(|module| ModuleNode::new(module, open_popup == Some(module.id)))
+                .collect(),
+        }
+    }
+}
+
+impl ModuleNode {
+    fn new(module: &ModuleRef, popup_open: bool) -> Self {
+        Self {
+            id: module.id,
+            module_type: module.module_type,
+            name: module.name.clone(),
+            classes: module
+                .root_widget
+                .css_classes()
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            location: module.location.clone(),
+            popup_open,
+        }
+    }
+}
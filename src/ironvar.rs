@@ -1,11 +1,20 @@
 #![doc = include_str!("../docs/Ironvars.md")]
 
 use crate::channels::SyncSenderExt;
-use crate::{arc_rw, read_lock, write_lock};
+use crate::config::matches_glob;
+use crate::script::Script;
+use crate::{arc_rw, lock, read_lock, spawn_blocking, spawn_shared, write_lock};
 use color_eyre::{Report, Result};
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use tokio::sync::broadcast;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once, OnceLock, RwLock};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{interval, sleep};
+use tracing::{debug, error, warn};
 
 pub type NamespaceTrait = Arc<dyn Namespace + Sync + Send>;
 
@@ -22,16 +31,56 @@ pub trait Namespace {
 
     fn namespaces(&self) -> Vec<String>;
     fn get_namespace(&self, key: &str) -> Option<NamespaceTrait>;
+
+    /// Subscribes to changes to any value in the namespace.
+    ///
+    /// Namespaces which cannot notify of changes return `None`,
+    /// and are polled instead while their values are in use.
+    fn subscribe(&self) -> Option<broadcast::Receiver<()>> {
+        None
+    }
+
+    /// Updates the namespace's values before it is polled.
+    fn refresh(&self) {}
 }
 
 pub trait WritableNamespace: Namespace {
     fn set(&self, key: &str, value: String) -> Result<()>;
+    fn remove(&self, key: &str) -> Result<()>;
+}
+
+/// A change to the value of an `IronVar`,
+/// sent to subscribers of all variables.
+#[derive(Debug, Clone)]
+pub struct VariableChange {
+    pub key: Box<str>,
+    pub value: Option<String>,
+    pub old_value: Option<String>,
 }
 
+/// A script to run when a variable whose key matches the pattern changes.
+type ChangeHook = (Box<str>, Script);
+
+/// How long to wait after a persistent variable changes before saving,
+/// so that changes in quick succession are written once.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// How often namespaces which cannot notify of changes
+/// are read while their values are in use.
+const NAMESPACE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Global singleton manager for `IronVar` variables.
 pub struct VariableManager {
     variables: Arc<RwLock<HashMap<Box<str>, IronVar>>>,
     namespaces: Arc<RwLock<HashMap<Box<str>, NamespaceTrait>>>,
+    persistent: Arc<RwLock<Vec<Box<str>>>>,
+    state: Arc<StateFile>,
+    save_tx: OnceLock<mpsc::UnboundedSender<()>>,
+    state_restored: Once,
+    hooks: Arc<RwLock<Vec<ChangeHook>>>,
+    hooks_started: Once,
+    changes_tx: broadcast::Sender<VariableChange>,
+    _changes_rx: broadcast::Receiver<VariableChange>,
 }
 
 impl Default for VariableManager {
@@ -42,21 +91,37 @@ impl Default for VariableManager {
 
 impl VariableManager {
     pub fn new() -> Self {
+        let (changes_tx, changes_rx) = broadcast::channel(64);
+
         Self {
             variables: arc_rw!(HashMap::new()),
             namespaces: arc_rw!(HashMap::new()),
+            persistent: arc_rw!(vec![]),
+            state: Arc::new(StateFile::new(state_path(None))),
+            save_tx: OnceLock::new(),
+            state_restored: Once::new(),
+            hooks: arc_rw!(vec![]),
+            hooks_started: Once::new(),
+            changes_tx,
+            _changes_rx: changes_rx,
         }
     }
 
     /// Subscribes to an `ironvar`, creating it if it does not exist.
     /// Any time the var is set, its value is sent on the channel.
-    pub fn subscribe(&self, key: Box<str>) -> broadcast::Receiver<Option<String>> {
+    pub fn subscribe(&self, key: Box<str>) -> broadcast::Receiver<Option<Value>> {
         write_lock!(self.variables)
             .entry(key)
             .or_insert_with(|| IronVar::new(None))
             .subscribe()
     }
 
+    /// Subscribes to changes to any `ironvar`.
+    /// Any time a var is set, its key and new value are sent on the channel.
+    pub fn subscribe_all(&self) -> broadcast::Receiver<VariableChange> {
+        self.changes_tx.subscribe()
+    }
+
     fn key_is_valid(key: &str) -> bool {
         !key.is_empty()
             && key
@@ -64,39 +129,459 @@ impl VariableManager {
                 .all(|char| char.is_alphanumeric() || char == '_' || char == '-')
     }
 
-    pub fn register_namespace<N>(&self, name: &str, namespace: Arc<N>)
+    /// Gets the value at `key`.
+    ///
+    /// The key may point into a namespace (`sysinfo.cpu_percent`),
+    /// or to a field inside a variable's JSON value (`build.status`).
+    pub fn get_value(&self, key: &str) -> Option<Value> {
+        let (name, rest) = key.split_once('.').unwrap_or((key, ""));
+
+        if let Some(mut ns) = self.get_namespace(name) {
+            let mut key = rest;
+            while let Some((part, rest)) = key.split_once('.')
+                && let Some(child) = ns.get_namespace(part)
+            {
+                ns = child;
+                key = rest;
+            }
+
+            return ns.get(key).map(Value::String);
+        }
+
+        let value = read_lock!(self.variables).get(name)?.get()?;
+        VariablePath::parse(key).resolve(&value).cloned()
+    }
+
+    /// Sets the value for a variable,
+    /// creating it if it does not exist.
+    pub fn set_value(&self, key: &str, value: Value) -> Result<()> {
+        self.check_set(key)?;
+
+        let mut change = VariableChange {
+            key: key.into(),
+            value: Some(value_to_string(&value)),
+            old_value: None,
+        };
+
+        if let Some(var) = write_lock!(self.variables).get_mut(&Box::from(key)) {
+            change.old_value = var.get().as_ref().map(value_to_string);
+            var.set(Some(value));
+        } else {
+            let var = IronVar::new(Some(value));
+            write_lock!(self.variables).insert(key.into(), var);
+        }
+
+        self.changes_tx.send_expect(change);
+
+        if self.is_persistent(key) {
+            self.save_state();
+        }
+
+        Ok(())
+    }
+
+    /// Removes the value of a variable.
+    /// Subscribers receive `None`.
+    pub fn remove_value(&self, key: &str) -> Result<()> {
+        self.check_remove(key)?;
+
+        let old_value = {
+            let mut variables = write_lock!(self.variables);
+            let var = variables
+                .get_mut(key)
+                .filter(|var| var.get().is_some())
+                .ok_or_else(|| Report::msg("Variable not found"))?;
+
+            let old_value = var.get();
+            var.set(None);
+            old_value
+        };
+
+        self.changes_tx.send_expect(VariableChange {
+            key: key.into(),
+            value: None,
+            old_value: old_value.as_ref().map(value_to_string),
+        });
+
+        if self.is_persistent(key) {
+            self.save_state();
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the variable `key` can be set, without setting it.
+    pub fn check_set(&self, key: &str) -> Result<()> {
+        if !Self::key_is_valid(key) {
+            return Err(Report::msg("Invalid key"));
+        }
+
+        if self.is_namespace(key) {
+            return Err(Report::msg("Key is a read-only namespace"));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the variable `key` can be removed, without removing it.
+    pub fn check_remove(&self, key: &str) -> Result<()> {
+        if self.is_namespace(key) {
+            return Err(Report::msg("Key is a read-only namespace"));
+        }
+
+        if read_lock!(self.variables)
+            .get(key)
+            .and_then(IronVar::get)
+            .is_none()
+        {
+            return Err(Report::msg("Variable not found"));
+        }
+
+        Ok(())
+    }
+
+    /// Registers a read-only namespace under `name`.
+    ///
+    /// Its values are also published as a JSON object
+    /// to subscribers of `name`, so they can be referenced
+    /// like any other variable (`#name.key`).
+    ///
+    /// Namespaces which cannot notify of changes are polled
+    /// while `name` has any subscribers.
+    pub fn register_namespace<N>(self: &Arc<Self>, name: &str, namespace: Arc<N>)
     where
         N: Namespace + Sync + Send + 'static,
     {
-        write_lock!(self.namespaces).insert(name.into(), namespace);
+        write_lock!(self.namespaces).insert(name.into(), namespace.clone());
+
+        let variable_manager = self.clone();
+        let name = Box::<str>::from(name);
+
+        match namespace.subscribe() {
+            Some(mut rx) => spawn_shared(async move {
+                variable_manager.publish_namespace(&name, &*namespace);
+
+                while let Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) = rx.recv().await {
+                    variable_manager.publish_namespace(&name, &*namespace);
+                }
+            }),
+            None => spawn_shared(async move {
+                let mut interval = interval(NAMESPACE_POLL_INTERVAL);
+
+                loop {
+                    interval.tick().await;
+
+                    if variable_manager.has_subscribers(&name) {
+                        namespace.refresh();
+                        variable_manager.publish_namespace(&name, &*namespace);
+                    }
+                }
+            }),
+        };
     }
-}
 
-impl Namespace for VariableManager {
-    fn get(&self, key: &str) -> Option<String> {
-        if key.contains('.') {
-            let (ns, key) = key.split_once('.')?;
+    /// Checks whether anything is subscribed to the variable `key`.
+    fn has_subscribers(&self, key: &str) -> bool {
+        read_lock!(self.variables)
+            .get(key)
+            .is_some_and(IronVar::has_subscribers)
+    }
 
-            let namespaces = read_lock!(self.namespaces);
-            let ns = namespaces.get(ns)?;
+    /// Sets the variable `name` to the current values of `namespace`,
+    /// notifying any subscribers.
+    fn publish_namespace(&self, name: &str, namespace: &(dyn Namespace + Sync + Send)) {
+        let value = namespace_to_value(namespace);
+        let mut old_value = None;
 
-            ns.get(key).as_deref().map(ToOwned::to_owned)
+        if let Some(var) = write_lock!(self.variables).get_mut(name) {
+            old_value = var.get();
+            if old_value.as_ref() == Some(&value) {
+                return;
+            }
+            var.set(Some(value.clone()));
         } else {
-            read_lock!(self.variables).get(key).and_then(IronVar::get)
+            write_lock!(self.variables).insert(name.into(), IronVar::new(Some(value.clone())));
+        }
+
+        self.changes_tx.send_expect(VariableChange {
+            key: name.into(),
+            value: Some(value_to_string(&value)),
+            old_value: old_value.as_ref().map(value_to_string),
+        });
+    }
+
+    /// Checks whether `key` is the name of a registered namespace.
+    fn is_namespace(&self, key: &str) -> bool {
+        read_lock!(self.namespaces).contains_key(key)
+    }
+
+    /// Sets the instance whose state file persistent variables are stored in.
+    /// This must be called before [`Self::set_persistent`].
+    pub fn set_instance(&self, instance: Option<&str>) {
+        *write_lock!(self.state.path) = state_path(instance);
+    }
+
+    /// Marks variables matching any of `patterns` as persistent.
+    ///
+    /// On the first call, their saved values are restored from the state file.
+    /// Later calls, such as on config reload, only replace the patterns,
+    /// so the current values are kept.
+    ///
+    /// Each pattern is a key, which may contain `*` and `?` wildcards.
+    pub fn set_persistent(&self, patterns: Vec<Box<str>>) {
+        *write_lock!(self.persistent) = patterns;
+
+        self.state_restored.call_once(|| {
+            let path = read_lock!(self.state.path).clone();
+            for (key, value) in read_state(&path) {
+                if self.is_persistent(&key) {
+                    debug!("Restoring persistent ironvar '{key}'");
+                    if self.set_value(&key, value).is_err() {
+                        warn!("Ignoring invalid persistent ironvar: '{key}'");
+                    }
+                }
+            }
+        });
+    }
+
+    /// Checks whether the variable `key` is persistent.
+    pub fn is_persistent(&self, key: &str) -> bool {
+        is_persistent(&read_lock!(self.persistent), key)
+    }
+
+    /// Sets the scripts to run when variables change,
+    /// replacing any previously set.
+    ///
+    /// Each hook is a key pattern (see [`matches_glob`]) and a script.
+    /// The script runs each time a matching variable's value changes,
+    /// with the key, old value and new value in the
+    /// `IRONVAR_KEY`, `IRONVAR_OLD_VALUE` and `IRONVAR_NEW_VALUE` environment variables.
+    /// Values which are not set are left out of the environment.
+    pub fn set_change_hooks(self: &Arc<Self>, hooks: Vec<ChangeHook>) {
+        *write_lock!(self.hooks) = hooks;
+
+        self.hooks_started.call_once(|| {
+            let hooks = self.hooks.clone();
+            let mut rx = self.subscribe_all();
+
+            spawn_shared(async move {
+                loop {
+                    match rx.recv().await {
+                        Ok(change) => run_change_hooks(&read_lock!(hooks), &change),
+                        Err(broadcast::error::RecvError::Lagged(count)) => {
+                            warn!("Ironvar change hooks lagged behind by {count} changes");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+        });
+    }
+
+    /// Schedules the values of all persistent variables to be written to the state file.
+    ///
+    /// The write happens on a blocking thread after [`SAVE_DELAY`],
+    /// so that any further changes in the meantime are included in the same write.
+    fn save_state(&self) {
+        self.state.dirty.store(true, Ordering::Release);
+
+        let tx = self.save_tx.get_or_init(|| {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+
+            let variables = self.variables.clone();
+            let persistent = self.persistent.clone();
+            let state = self.state.clone();
+
+            spawn_shared(async move {
+                while rx.recv().await.is_some() {
+                    sleep(SAVE_DELAY).await;
+                    while rx.try_recv().is_ok() {}
+
+                    if !state.dirty.swap(false, Ordering::AcqRel) {
+                        continue;
+                    }
+
+                    let values = persistent_values(&read_lock!(variables), &read_lock!(persistent));
+
+                    let state = state.clone();
+                    if let Err(err) = spawn_blocking(move || state.write(&values)).await {
+                        error!("{err:?}");
+                    }
+                }
+            });
+
+            tx
+        });
+
+        // the receiver only closes if the runtime is shutting down
+        tx.send(()).ok();
+    }
+
+    /// Immediately writes any persistent variable changes which have not been saved yet.
+    /// This should be called before exiting.
+    pub fn flush_state(&self) {
+        if self.state.dirty.swap(false, Ordering::AcqRel) {
+            let values =
+                persistent_values(&read_lock!(self.variables), &read_lock!(self.persistent));
+            self.state.write(&values);
+        }
+    }
+}
+
+/// The file persistent variables are saved to.
+#[derive(Debug)]
+struct StateFile {
+    path: RwLock<PathBuf>,
+    /// Whether there are changes which have not been written yet.
+    dirty: AtomicBool,
+    /// Held while writing, so that writes cannot interleave.
+    writing: Mutex<()>,
+}
+
+impl StateFile {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path: RwLock::new(path),
+            dirty: AtomicBool::new(false),
+            writing: Mutex::new(()),
+        }
+    }
+
+    fn write(&self, values: &BTreeMap<Box<str>, Value>) {
+        let _guard = lock!(self.writing);
+        let path = read_lock!(self.path).clone();
+
+        if let Err(err) = write_state(&path, values) {
+            error!("{:?}", err.wrap_err("Failed to save persistent ironvars"));
+        }
+    }
+}
+
+/// Checks whether `key` matches any of the persistent `patterns`.
+fn is_persistent(patterns: &[Box<str>], key: &str) -> bool {
+    patterns.iter().any(|pattern| matches_glob(pattern, key))
+}
+
+/// Gets the current value of each persistent variable.
+fn persistent_values(
+    variables: &HashMap<Box<str>, IronVar>,
+    patterns: &[Box<str>],
+) -> BTreeMap<Box<str>, Value> {
+    variables
+        .iter()
+        .filter(|(key, _)| is_persistent(patterns, key))
+        .filter_map(|(key, var)| var.get().map(|value| (key.clone(), value)))
+        .collect()
+}
+
+/// Converts the values of `namespace` and its children
+/// into a JSON object.
+fn namespace_to_value(namespace: &(dyn Namespace + Sync + Send)) -> Value {
+    let mut map = namespace
+        .get_all()
+        .into_iter()
+        .map(|(key, value)| (key.into(), Value::String(value)))
+        .collect::<serde_json::Map<_, _>>();
+
+    for name in namespace.namespaces() {
+        if let Some(child) = namespace.get_namespace(&name) {
+            map.insert(name, namespace_to_value(&*child));
+        }
+    }
+
+    Value::Object(map)
+}
+
+/// Runs the script for each hook whose pattern matches the changed key,
+/// unless the value is unchanged.
+fn run_change_hooks(hooks: &[ChangeHook], change: &VariableChange) {
+    if change.value == change.old_value {
+        return;
+    }
+
+    let mut env = vec![("IRONVAR_KEY", change.key.to_string())];
+    if let Some(old_value) = &change.old_value {
+        env.push(("IRONVAR_OLD_VALUE", old_value.clone()));
+    }
+    if let Some(value) = &change.value {
+        env.push(("IRONVAR_NEW_VALUE", value.clone()));
+    }
+
+    for (pattern, script) in hooks {
+        if matches_glob(pattern, &change.key) {
+            debug!(
+                "Running change hook '{pattern}' for ironvar '{}'",
+                change.key
+            );
+            script.run_as_oneshot_with_env(None, env.clone());
         }
     }
+}
+
+/// Gets the path to the file persistent variables are stored in.
+///
+/// Each named instance has its own file.
+fn state_path(instance: Option<&str>) -> PathBuf {
+    let file_name = match instance {
+        None | Some(crate::DEFAULT_INSTANCE) => "ironvars.json".to_string(),
+        Some(instance) => format!("ironvars-{instance}.json"),
+    };
+
+    dirs::state_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("ironbar")
+        .join(file_name)
+}
+
+/// Reads the saved variables from the state file at `path`.
+/// A missing or invalid file is treated as empty.
+fn read_state(path: &Path) -> HashMap<String, Value> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|err| {
+            warn!(
+                "Ignoring invalid ironvar state file {}: {err}",
+                path.display()
+            );
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Writes `values` to the state file at `path`.
+///
+/// The file is written to a temporary file first, then moved into place,
+/// so an existing file is never left partially written.
+fn write_state(path: &Path, values: &BTreeMap<Box<str>, Value>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_string_pretty(values)?)?;
+    fs::rename(tmp_path, path)?;
+
+    Ok(())
+}
+
+impl Namespace for VariableManager {
+    fn get(&self, key: &str) -> Option<String> {
+        self.get_value(key).as_ref().map(value_to_string)
+    }
 
     fn list(&self) -> Vec<String> {
         read_lock!(self.variables)
-            .keys()
-            .map(ToString::to_string)
+            .iter()
+            .filter(|(key, var)| !self.is_namespace(key) && var.get().is_some())
+            .map(|(key, _)| key.to_string())
             .collect()
     }
 
     fn get_all(&self) -> HashMap<Box<str>, String> {
         read_lock!(self.variables)
             .iter()
-            .filter_map(|(k, v)| v.get().map(|value| (k.clone(), value)))
+            .filter(|(key, _)| !self.is_namespace(key))
+            .filter_map(|(k, v)| v.get().map(|value| (k.clone(), value_to_string(&value))))
             .collect()
     }
 
@@ -116,33 +601,190 @@ impl WritableNamespace for VariableManager {
     /// Sets the value for a variable,
     /// creating it if it does not exist.
     fn set(&self, key: &str, value: String) -> Result<()> {
-        if Self::key_is_valid(key) {
-            if let Some(var) = write_lock!(self.variables).get_mut(&Box::from(key)) {
-                var.set(Some(value));
-            } else {
-                let var = IronVar::new(Some(value));
-                write_lock!(self.variables).insert(key.into(), var);
+        self.set_value(key, Value::String(value))
+    }
+
+    /// Removes the value of a variable.
+    fn remove(&self, key: &str) -> Result<()> {
+        self.remove_value(key)
+    }
+}
+
+/// A snapshot of the values in a namespace,
+/// and the values in each of its child namespaces.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceState {
+    values: BTreeMap<Box<str>, String>,
+    namespaces: BTreeMap<Box<str>, NamespaceState>,
+}
+
+impl NamespaceState {
+    /// Sets the value at `key`.
+    pub fn insert(&mut self, key: &str, value: impl ToString) {
+        self.values.insert(key.into(), value.to_string());
+    }
+
+    /// Sets the value at `key` if `value` is `Some`,
+    /// otherwise removes it.
+    pub fn insert_opt(&mut self, key: &str, value: Option<impl ToString>) {
+        match value {
+            Some(value) => self.insert(key, value),
+            None => {
+                self.values.remove(key);
             }
+        }
+    }
 
-            Ok(())
-        } else {
-            Err(Report::msg("Invalid key"))
+    /// Sets the child namespace at `key`.
+    pub fn insert_namespace(&mut self, key: &str, namespace: NamespaceState) {
+        self.namespaces.insert(key.into(), namespace);
+    }
+}
+
+impl Namespace for NamespaceState {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn list(&self) -> Vec<String> {
+        self.values.keys().map(ToString::to_string).collect()
+    }
+
+    fn namespaces(&self) -> Vec<String> {
+        self.namespaces.keys().map(ToString::to_string).collect()
+    }
+
+    fn get_namespace(&self, key: &str) -> Option<NamespaceTrait> {
+        self.namespaces
+            .get(key)
+            .map(|namespace| Arc::new(namespace.clone()) as NamespaceTrait)
+    }
+}
+
+/// A read-only namespace exposing the state of a client.
+///
+/// The client replaces the state each time it changes,
+/// which notifies any subscribers.
+#[derive(Debug)]
+pub struct StateNamespace {
+    state: RwLock<NamespaceState>,
+    tx: broadcast::Sender<()>,
+    _rx: broadcast::Receiver<()>,
+}
+
+impl Default for StateNamespace {
+    fn default() -> Self {
+        let (tx, rx) = broadcast::channel(8);
+
+        Self {
+            state: RwLock::new(NamespaceState::default()),
+            tx,
+            _rx: rx,
         }
     }
 }
 
+impl StateNamespace {
+    /// Replaces the current state,
+    /// notifying subscribers if it has changed.
+    pub fn set(&self, state: NamespaceState) {
+        let mut current = write_lock!(self.state);
+        if *current != state {
+            *current = state;
+            self.tx.send_expect(());
+        }
+    }
+
+    /// Updates the current state in place using `f`,
+    /// notifying subscribers if it has changed.
+    pub fn update<F>(&self, f: F)
+    where
+        F: FnOnce(&mut NamespaceState),
+    {
+        let mut state = read_lock!(self.state).clone();
+        f(&mut state);
+        self.set(state);
+    }
+}
+
+impl Namespace for StateNamespace {
+    fn get(&self, key: &str) -> Option<String> {
+        read_lock!(self.state).get(key)
+    }
+
+    fn list(&self) -> Vec<String> {
+        read_lock!(self.state).list()
+    }
+
+    fn namespaces(&self) -> Vec<String> {
+        read_lock!(self.state).namespaces()
+    }
+
+    fn get_namespace(&self, key: &str) -> Option<NamespaceTrait> {
+        read_lock!(self.state).get_namespace(key)
+    }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<()>> {
+        Some(self.tx.subscribe())
+    }
+}
+
+/// A reference to an `IronVar`,
+/// with an optional path to a field inside its JSON value.
+///
+/// Written as `key.path.to.field`.
+/// Array items are accessed by index, for example `events.0.title`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariablePath {
+    pub key: Box<str>,
+    pub path: Vec<Box<str>>,
+}
+
+impl VariablePath {
+    pub fn parse(input: &str) -> Self {
+        let mut parts = input.split('.');
+        let key = parts.next().unwrap_or_default().into();
+        let path = parts.map(Into::into).collect();
+
+        Self { key, path }
+    }
+
+    /// Gets the field this path points to inside `value`,
+    /// if it exists.
+    pub fn resolve<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.path.iter().try_fold(value, |value, part| match value {
+            Value::Object(map) => map.get(part.as_ref()),
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+}
+
+/// Converts a variable value to a string for display.
+///
+/// Strings are returned as-is, without quotes,
+/// and `null` becomes an empty string.
+/// Any other value is returned as JSON.
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(str) => str.clone(),
+        value => value.to_string(),
+    }
+}
+
 /// Ironbar dynamic variable representation.
 /// Interact with them through the `VARIABLE_MANAGER` `VariableManager` singleton.
 #[derive(Debug)]
 pub struct IronVar {
-    value: Option<String>,
-    tx: broadcast::Sender<Option<String>>,
-    _rx: broadcast::Receiver<Option<String>>,
+    value: Option<Value>,
+    tx: broadcast::Sender<Option<Value>>,
+    _rx: broadcast::Receiver<Option<Value>>,
 }
 
 impl IronVar {
     /// Creates a new variable.
-    fn new(value: Option<String>) -> Self {
+    fn new(value: Option<Value>) -> Self {
         let (tx, rx) = broadcast::channel(32);
 
         Self { value, tx, _rx: rx }
@@ -150,22 +792,147 @@ impl IronVar {
 
     /// Gets the current variable value.
     /// Prefer to subscribe to changes where possible.
-    pub fn get(&self) -> Option<String> {
+    pub fn get(&self) -> Option<Value> {
         self.value.clone()
     }
 
     /// Sets the current variable value.
     /// The change is broadcast to all receivers.
-    fn set(&mut self, value: Option<String>) {
+    fn set(&mut self, value: Option<Value>) {
         self.value.clone_from(&value);
         self.tx.send_expect(value);
     }
 
     /// Subscribes to the variable.
     /// The latest value is immediately sent to all receivers.
-    fn subscribe(&self) -> broadcast::Receiver<Option<String>> {
+    fn subscribe(&self) -> broadcast::Receiver<Option<Value>> {
         let rx = self.tx.subscribe();
         self.tx.send_expect(self.value.clone());
         rx
     }
+
+    /// Checks whether the variable has any receivers,
+    /// other than the one held to keep the channel open.
+    fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_path() {
+        let value =
+            serde_json::json!({ "build": { "status": "ok" }, "events": [{ "title": "standup" }] });
+
+        let resolve = |input: &str| VariablePath::parse(input).resolve(&value).cloned();
+
+        assert_eq!(resolve("var"), Some(value.clone()));
+        assert_eq!(resolve("var.build.status"), Some(Value::from("ok")));
+        assert_eq!(resolve("var.events.0.title"), Some(Value::from("standup")));
+        assert_eq!(resolve("var.events.1.title"), None);
+        assert_eq!(resolve("var.build.status.missing"), None);
+    }
+
+    #[test]
+    fn test_namespace_to_value() {
+        let mut child = NamespaceState::default();
+        child.insert("volume", 50);
+        child.insert("muted", false);
+
+        let mut state = NamespaceState::default();
+        state.insert("count", 2);
+        state.insert_opt("missing", None::<String>);
+        state.insert_namespace("default_sink", child);
+
+        assert_eq!(
+            namespace_to_value(&state),
+            serde_json::json!({
+                "count": "2",
+                "default_sink": { "volume": "50", "muted": "false" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_namespace_expression() {
+        use crate::dynamic_value::Expression;
+
+        let variable_manager = VariableManager::new();
+        variable_manager
+            .set_value("dnd", Value::from("false"))
+            .expect("to be valid");
+
+        let expression = "#sysinfo.cpu_percent.mean > 80 && #sysinfo.memory_percent < 50 && !#dnd"
+            .parse::<Expression>()
+            .expect("to be valid");
+
+        // mirrors `Expression::watch`, which receives the current value on subscribing
+        let evaluate = |state: &NamespaceState| {
+            variable_manager.publish_namespace("sysinfo", state);
+
+            let values = expression
+                .variables()
+                .into_iter()
+                .map(|key| {
+                    let value = variable_manager
+                        .subscribe(key.clone())
+                        .try_recv()
+                        .expect("to receive current value");
+
+                    (key, value)
+                })
+                .collect();
+
+            expression.evaluate(&values)
+        };
+
+        let state = |cpu_percent: f64| {
+            let mut cpu = NamespaceState::default();
+            cpu.insert("mean", cpu_percent);
+
+            let mut state = NamespaceState::default();
+            state.insert("memory_percent", 40);
+            state.insert_namespace("cpu_percent", cpu);
+            state
+        };
+
+        assert_eq!(evaluate(&state(85.5)), Value::Bool(true));
+        assert_eq!(evaluate(&state(20.0)), Value::Bool(false));
+    }
+
+    #[test]
+    fn test_check_does_not_change() {
+        let variable_manager = VariableManager::new();
+        variable_manager.publish_namespace("sysinfo", &NamespaceState::default());
+
+        assert!(variable_manager.check_set("foo").is_ok());
+        assert!(variable_manager.check_set("sysinfo.cpu").is_err());
+        assert!(variable_manager.check_remove("foo").is_err());
+        assert!(variable_manager.get_value("foo").is_none());
+
+        variable_manager
+            .set_value("foo", Value::from("bar"))
+            .expect("to be valid");
+
+        assert!(variable_manager.check_remove("foo").is_ok());
+        assert_eq!(variable_manager.get_value("foo"), Some(Value::from("bar")));
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let path = std::env::temp_dir()
+            .join(format!("ironbar-test-{}", std::process::id()))
+            .join("ironvars.json");
+
+        let values = BTreeMap::from([(Box::from("foo"), Value::from("bar"))]);
+        write_state(&path, &values).expect("to write state");
+
+        let state = read_state(&path);
+        assert_eq!(state.get("foo"), Some(&Value::from("bar")));
+
+        fs::remove_dir_all(path.parent().expect("to have parent")).ok();
+    }
 }
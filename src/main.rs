@@ -18,6 +18,8 @@ use gtk::gdk::{Display, Monitor};
 use gtk::prelude::*;
 use smithay_client_toolkit::output::OutputInfo;
 use tokio::runtime::Runtime;
+#[cfg(feature = "ipc")]
+use tokio::sync::broadcast;
 use tokio::task::{JoinHandle, block_in_place};
 use tracing::{debug, error, info};
 
@@ -25,11 +27,12 @@ use crate::bar::{Bar, create_bar};
 use crate::channels::SyncSenderExt;
 use crate::clients::Clients;
 use crate::clients::outputs::MonitorState;
-use crate::config::{Config, ConfigLocation, MonitorConfig};
+use crate::config::{BarConfig, Config, ConfigLocation, ConfigWatcher, MonitorConfig};
 use crate::desktop_file::DesktopFiles;
 use crate::error::ExitCode;
 #[cfg(feature = "ipc")]
 use crate::ironvar::VariableManager;
+use crate::script::ScriptRegistry;
 use crate::style::{CssSource, load_css};
 
 mod bar;
@@ -55,6 +58,10 @@ mod script;
 mod style;
 
 pub const APP_ID: &str = "dev.jstanger.ironbar";
+
+/// The name of the instance which is used
+/// when no instance name is specified.
+pub const DEFAULT_INSTANCE: &str = "default";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn main() {
@@ -63,7 +70,12 @@ fn main() {
             run_with_args();
         } else {
             let config_location = ConfigLocation::from_env("IRONBAR_CONFIG").unwrap_or_default();
-            start_ironbar(false, config_location, ConfigLocation::from_env("IRONBAR_CSS"));
+            start_ironbar(
+                false,
+                config_location,
+                ConfigLocation::from_env("IRONBAR_CSS"),
+                env::var("IRONBAR_INSTANCE").ok(),
+            );
         }
     }
 }
@@ -98,21 +110,59 @@ fn run_with_args() {
     }
 
     match args.command {
-        Some(command) => {
+        #[cfg(feature = "config")]
+        Some(ipc::Command::Check { path }) => {
+            let location = path
+                .map(ConfigLocation::Custom)
+                .or(args.config)
+                .unwrap_or_default();
+
+            let report = config::validate::validate(&location);
+            cli::handle_config_report(report, args.format.unwrap_or_default());
+        }
+        Some(ipc::Command::Instances) => cli::handle_response(
+            ipc::Response::json(&ipc::Ipc::instances()),
+            args.format.unwrap_or_default(),
+        ),
+        Some(mut command) => {
+            if let ipc::Command::Batch { commands } = &mut command
+                && commands.is_empty()
+            {
+                match cli::read_batch(std::io::stdin().lock()) {
+                    Ok(batch) => *commands = batch,
+                    Err(err) => {
+                        error!("{err:#}");
+                        exit(ExitCode::IpcResponseError as i32)
+                    }
+                }
+            }
+
             if args.debug {
                 eprintln!("REQUEST: {command:?}");
             }
 
+            let format = args.format.unwrap_or_default();
+
             let rt = create_runtime();
             rt.block_on(async move {
-                let ipc = ipc::Ipc::new();
-                match ipc.send(command, args.debug).await {
+                let ipc = ipc::Ipc::new(args.instance.as_deref());
+
+                let res = if command.is_stream() {
+                    ipc.subscribe(command, args.debug, |event| {
+                        cli::handle_event(event, format);
+                    })
+                    .await
+                } else {
+                    ipc.send(command, args.debug).await
+                };
+
+                match res {
                     Ok(res) => {
                         if args.debug {
                             eprintln!("RESPONSE: {res:?}");
                         }
 
-                        cli::handle_response(res, args.format.unwrap_or_default());
+                        cli::handle_response(res, format);
                     }
                     Err(err) => {
                         error!("{err:#}");
@@ -121,7 +171,12 @@ fn run_with_args() {
                 }
             });
         }
-        None => start_ironbar(args.debug, args.config.unwrap_or_default(), args.theme),
+        None => start_ironbar(
+            args.debug,
+            args.config.unwrap_or_default(),
+            args.theme,
+            args.instance,
+        ),
     }
 }
 
@@ -132,15 +187,20 @@ pub struct Ironbar {
     config: Rc<RefCell<Config>>,
     css_source: Rc<CssSource>,
     config_location: ConfigLocation,
-    css_location: Option<ConfigLocation>,
+    config_watcher: RefCell<Option<ConfigWatcher>>,
+    instance: Option<String>,
 
     desktop_files: DesktopFiles,
     image_provider: image::Provider,
 }
 
 impl Ironbar {
-    fn new(config_location: ConfigLocation, css_location: Option<ConfigLocation>) -> Self {
-        let (mut config, css_source) = Config::load(config_location.clone(), css_location.clone());
+    fn new(
+        config_location: ConfigLocation,
+        css_location: Option<ConfigLocation>,
+        instance: Option<String>,
+    ) -> Self {
+        let (mut config, css_source) = Config::load(config_location.clone(), css_location);
 
         let desktop_files = DesktopFiles::new();
         let image_provider =
@@ -152,7 +212,8 @@ impl Ironbar {
             config: rc_mut!(config),
             css_source: Rc::new(css_source),
             config_location,
-            css_location,
+            config_watcher: RefCell::new(None),
+            instance,
             desktop_files,
             image_provider,
         }
@@ -162,7 +223,9 @@ impl Ironbar {
         info!("Ironbar version {}", VERSION);
         info!("Starting application");
 
-        let app = Application::builder().application_id(APP_ID).build();
+        let app = Application::builder()
+            .application_id(application_id(self.instance.as_deref()))
+            .build();
 
         let running = AtomicBool::new(false);
 
@@ -186,26 +249,35 @@ impl Ironbar {
 
             running.store(true, Ordering::Relaxed);
 
-            cfg_if! {
-                if #[cfg(feature = "ipc")] {
-                    let ipc = ipc::Ipc::new();
-                    ipc.start(app, instance.clone());
+            // the socket is only removed on shutdown if this instance owns it.
+            #[cfg(feature = "ipc")]
+            let ipc_path = {
+                let ipc = ipc::Ipc::new(instance.instance.as_deref());
+                match ipc.start(app, instance.clone()) {
+                    Ok(()) => Some(ipc.path().to_path_buf()),
+                    Err(err) => {
+                        error!("{err:?}");
+                        None
+                    }
                 }
-            }
+            };
 
             load_css(&css_source);
 
             let (tx, rx) = mpsc::channel();
 
-            #[cfg(feature = "ipc")]
-            let ipc_path = ipc.path().to_path_buf();
             spawn_blocking(move || {
                 rx.recv().expect("to receive from channel");
 
                 info!("Shutting down");
 
                 #[cfg(feature = "ipc")]
-                ipc::Ipc::shutdown(ipc_path);
+                if let Some(ipc_path) = ipc_path {
+                    ipc::Ipc::shutdown(ipc_path);
+                }
+
+                #[cfg(feature = "ipc")]
+                Ironbar::variable_manager().flush_state();
 
                 exit(0);
             });
@@ -229,6 +301,9 @@ impl Ironbar {
                     .image_provider
                     .set_icon_theme(instance.config.borrow().icon_theme.as_deref());
 
+                #[cfg(feature = "ipc")]
+                instance.init_namespaces();
+
                 // Load initial bars
                 match load_output_bars(&instance.clone(), &app) {
                     Ok(_) => {}
@@ -238,6 +313,8 @@ impl Ironbar {
                     }
                 };
 
+                instance.watch_config(&app);
+
                 let outputs = instance.clients.borrow_mut().outputs();
                 let mut rx_outputs = outputs.subscribe();
 
@@ -254,14 +331,11 @@ impl Ironbar {
                                 .for_each(Bar::close);
                         }
                         MonitorState::Connected(wl_output, gdk_output) => {
-                            if let Some(gdk_output) = gdk_output.upgrade() {
-                                match load_output_bars_for(&instance, &app, &wl_output, &gdk_output)
-                                {
-                                    Ok(mut new_bars) => {
-                                        instance.bars.borrow_mut().append(&mut new_bars);
-                                    }
-                                    Err(err) => error!("{err:?}"),
-                                }
+                            if let Some(gdk_output) = gdk_output.upgrade()
+                                && let Err(err) =
+                                    load_output_bars_for(&instance, &app, &wl_output, &gdk_output)
+                            {
+                                error!("{err:?}");
                             }
                         }
                     }
@@ -299,6 +373,24 @@ impl Ironbar {
             .clone()
     }
 
+    /// Gets the shared script registry singleton.
+    #[must_use]
+    pub fn script_registry() -> Arc<ScriptRegistry> {
+        static SCRIPT_REGISTRY: OnceLock<Arc<ScriptRegistry>> = OnceLock::new();
+        SCRIPT_REGISTRY
+            .get_or_init(|| Arc::new(ScriptRegistry::new()))
+            .clone()
+    }
+
+    /// Gets the sender for the IPC event bus,
+    /// which streams events to subscribed IPC clients.
+    #[cfg(feature = "ipc")]
+    #[must_use]
+    pub fn ipc_events() -> broadcast::Sender<ipc::Event> {
+        static IPC_EVENTS: OnceLock<broadcast::Sender<ipc::Event>> = OnceLock::new();
+        IPC_EVENTS.get_or_init(|| broadcast::channel(64).0).clone()
+    }
+
     #[must_use]
     pub fn desktop_files(&self) -> DesktopFiles {
         self.desktop_files.clone()
@@ -323,12 +415,75 @@ impl Ironbar {
             .collect()
     }
 
-    /// Re-reads the config file from disk and replaces the active config.
-    /// Note this does *not* reload bars, which must be performed separately.
+    /// Re-reads the config file from disk and applies it to the bars.
+    ///
+    /// Only bars and modules whose config has changed are recreated.
+    ///
+    /// The new config is validated before any bars are changed.
+    /// If it is invalid, the active config and bars are kept
+    /// and an error is returned.
+    fn reload(self: &Rc<Self>, app: &Application) -> Result<()> {
+        let config = Config::try_load(&self.config_location)?;
+        self.config.replace(config);
+
+        #[cfg(feature = "ipc")]
+        self.init_namespaces();
+
+        info!("Updating bars");
+        let res = load_output_bars(self, app);
+
+        #[cfg(feature = "ipc")]
+        ipc::events::emit(ipc::Event::Reload);
+
+        self.watch_config(app);
+        res
+    }
+
+    /// Creates the clients for each namespace in the `ironvar_namespaces` option,
+    /// so their values are available without a module using them.
     #[cfg(feature = "ipc")]
-    fn reload_config(&self) {
-        self.config
-            .replace(Config::load(self.config_location.clone(), self.css_location.clone()).0);
+    fn init_namespaces(&self) {
+        let names = self.config.borrow().ironvar_namespaces.clone();
+
+        let mut clients = self.clients.borrow_mut();
+        for name in names {
+            if let Err(err) = clients.init_namespace(&name) {
+                error!("Failed to initialize ironvar namespace '{name}': {err:?}");
+            }
+        }
+    }
+
+    /// Starts or stops watching the config files for changes,
+    /// depending on the `watch_config` option.
+    fn watch_config(self: &Rc<Self>, app: &Application) {
+        let paths = {
+            let config = self.config.borrow();
+            if config.watch_config {
+                config.sources.clone()
+            } else {
+                vec![]
+            }
+        };
+
+        let mut watcher = self.config_watcher.borrow_mut();
+
+        if watcher.is_none() && paths.is_empty() {
+            return;
+        }
+
+        watcher
+            .get_or_insert_with(|| {
+                let ironbar = self.clone();
+                let app = app.clone();
+
+                ConfigWatcher::new(move || {
+                    info!("Config changed, reloading");
+                    if let Err(err) = ironbar.reload(&app) {
+                        error!("Failed to reload config: {err}");
+                    }
+                })
+            })
+            .watch(paths);
     }
 }
 
@@ -336,13 +491,60 @@ fn start_ironbar(
     debug: bool,
     config_location: ConfigLocation,
     css_location: Option<ConfigLocation>,
+    instance: Option<String>,
 ) {
+    let instance = match instance.as_deref().map(parse_instance).transpose() {
+        Ok(instance) => instance,
+        Err(err) => {
+            eprintln!("Invalid instance name: {err}");
+            exit(ExitCode::InvalidInstance as i32)
+        }
+    };
+
+    if let Some(instance) = &instance {
+        // SAFETY: called before any other threads are started.
+        // This ensures the CLI targets this instance when called from scripts.
+        unsafe { env::set_var("IRONBAR_INSTANCE", instance) };
+    }
+
+    #[cfg(feature = "ipc")]
+    Ironbar::variable_manager().set_instance(instance.as_deref());
+
     let _guard = logging::install_logging(debug);
 
-    let ironbar = Ironbar::new(config_location, css_location);
+    let ironbar = Ironbar::new(config_location, css_location, instance);
     ironbar.start();
 }
 
+/// Checks that an instance name is safe to use in file names.
+pub fn parse_instance(value: &str) -> Result<String, String> {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(value.to_string())
+    } else {
+        Err("must only contain letters, numbers, `-` and `_`".to_string())
+    }
+}
+
+/// Gets the GTK application ID.
+///
+/// Named instances get their own ID,
+/// as GTK otherwise treats them as the same running application.
+fn application_id(instance: Option<&str>) -> String {
+    match instance {
+        None | Some(DEFAULT_INSTANCE) => APP_ID.to_string(),
+        Some(instance) => {
+            // `-` is discouraged in IDs, so is escaped along with `_`
+            // to keep each instance's ID distinct.
+            let escaped = instance.replace('_', "__").replace('-', "_0");
+            format!("{APP_ID}.instance_{escaped}")
+        }
+    }
+}
+
 /// Gets the GDK `Display` instance.
 #[must_use]
 pub fn get_display() -> Display {
@@ -353,64 +555,87 @@ pub fn get_display() -> Display {
     })
 }
 
+/// Gets the bar configs for an output.
+fn bar_configs_for(config: &Config, monitor_name: &str, monitor_desc: &str) -> Vec<BarConfig> {
+    let show_default_bar =
+        config.bar.start.is_some() || config.bar.center.is_some() || config.bar.end.is_some();
+
+    let configs = match config.monitor_config(monitor_name, monitor_desc) {
+        Some(MonitorConfig::Single(config)) => vec![config.clone()],
+        Some(MonitorConfig::Multiple(configs)) => configs.clone(),
+        None if show_default_bar => vec![config.bar.clone()],
+        None => vec![],
+    };
+
+    // extending bars are merged while loading the config,
+    // so are left as-is without the `config` feature.
+    #[cfg(not(feature = "config"))]
+    if configs.iter().any(|bar| bar.extend) {
+        error!(
+            "Bar on '{monitor_name}' sets `extend`, which requires the `config` feature. It will not extend the top-level bar."
+        );
+    }
+
+    configs
+}
+
 /// Loads all the bars associated with an output.
+///
+/// Existing bars on the output are updated in place where possible,
+/// so only bars and modules whose config has changed are recreated.
 fn load_output_bars_for(
     ironbar: &Rc<Ironbar>,
     app: &Application,
     output: &OutputInfo,
     monitor: &Monitor,
-) -> Result<Vec<Bar>> {
+) -> Result<()> {
     let Some(monitor_name) = &output.name else {
         return Err(Report::msg("Output missing monitor name"));
     };
 
     let monitor_desc = &output.description.clone().unwrap_or_default();
 
-    let config = ironbar.config.borrow();
+    let configs = bar_configs_for(&ironbar.config.borrow(), monitor_name, monitor_desc);
 
-    let show_default_bar =
-        config.bar.start.is_some() || config.bar.center.is_some() || config.bar.end.is_some();
+    // take the bars out while updating,
+    // as modules may access the list while being created.
+    let mut existing = ironbar
+        .bars
+        .borrow_mut()
+        .extract_if(.., |bar| bar.monitor_name() == monitor_name)
+        .collect::<Vec<_>>()
+        .into_iter();
+
+    let mut bars = vec![];
 
-    let bars = match config.monitors.as_ref().and_then(|config| {
-        config.get(monitor_name).or_else(|| {
-            config
-                .keys()
-                .find(|&k| monitor_desc.to_lowercase().starts_with(&k.to_lowercase()))
-                .and_then(|key| config.get(key))
-        })
-    }) {
-        Some(MonitorConfig::Single(config)) => {
-            vec![create_bar(
-                app,
-                monitor,
-                monitor_name.to_string(),
-                config.clone(),
-                ironbar.clone(),
-            )]
+    for config in configs {
+        if let Some(bar) = existing.next() {
+            if bar.update(&config) {
+                bars.push(bar);
+                continue;
+            }
+
+            info!("Recreating bar '{}'", bar.name());
+            bar.close();
         }
-        Some(MonitorConfig::Multiple(configs)) => configs
-            .iter()
-            .map(|config| {
-                create_bar(
-                    app,
-                    monitor,
-                    monitor_name.to_string(),
-                    config.clone(),
-                    ironbar.clone(),
-                )
-            })
-            .collect(),
-        None if show_default_bar => vec![create_bar(
+
+        bars.push(create_bar(
             app,
             monitor,
             monitor_name.to_string(),
-            config.bar.clone(),
+            config,
             ironbar.clone(),
-        )],
-        None => vec![],
-    };
+        ));
+    }
+
+    for bar in existing {
+        info!("Closing bar '{}'", bar.name());
+        bar.close();
+    }
+
+    ironbar.bars.borrow_mut().append(&mut bars);
 
-    Ok(bars)
+    Ok(())
 }
 
 pub fn load_output_bars(ironbar: &Rc<Ironbar>, app: &Application) -> Result<()> {
@@ -446,9 +671,8 @@ pub fn load_output_bars(ironbar: &Rc<Ironbar>, app: &Application) -> Result<()>
             continue;
         };
 
-        match crate::load_output_bars_for(ironbar, app, &output, &monitor) {
-            Ok(mut bars) => ironbar.bars.borrow_mut().append(&mut bars),
-            Err(err) => error!("{err:?}"),
+        if let Err(err) = load_output_bars_for(ironbar, app, &output, &monitor) {
+            error!("{err:?}");
         }
     }
 
@@ -463,7 +687,24 @@ fn create_runtime() -> Runtime {
 }
 
 /// Calls `spawn` on the Tokio runtime.
+///
+/// Tasks spawned while a module is being created
+/// are aborted when that module is removed.
 pub fn spawn<F>(f: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let handle = Ironbar::runtime().spawn(f);
+    modules::ModuleTasks::track(handle.abort_handle());
+    handle
+}
+
+/// Calls `spawn` on the Tokio runtime,
+/// without tying the task to any module being created.
+///
+/// Use for tasks shared between modules.
+pub fn spawn_shared<F>(f: F) -> JoinHandle<F::Output>
 where
     F: Future + Send + 'static,
     F::Output: Send + 'static,
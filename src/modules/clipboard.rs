@@ -7,6 +7,7 @@ use crate::gtk_helpers::IronbarPaintableExt;
 use crate::image::IconButton;
 use crate::modules::{
     Module, ModuleInfo, ModuleParts, ModulePopup, ModuleUpdateEvent, PopupButton, WidgetContext,
+    unknown_action,
 };
 use crate::{module_impl, spawn};
 use gtk::gdk::{BUTTON_PRIMARY, Texture};
@@ -18,7 +19,7 @@ use std::ops::Deref;
 use tokio::sync::mpsc;
 use tracing::{debug, error};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct ClipboardModule {
@@ -92,6 +93,7 @@ pub enum ControllerEvent {
 pub enum UIEvent {
     Copy(usize),
     Remove(usize),
+    Clear,
 }
 
 impl Module<Button> for ClipboardModule {
@@ -100,6 +102,13 @@ impl Module<Button> for ClipboardModule {
 
     module_impl!("clipboard");
 
+    fn parse_action(action: &str, _args: &[String]) -> color_eyre::Result<Self::ReceiveMessage> {
+        match action {
+            "clear" => Ok(UIEvent::Clear),
+            _ => Err(unknown_action(action, &["clear"])),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
@@ -144,6 +153,7 @@ impl Module<Button> for ClipboardModule {
                 match event {
                     UIEvent::Copy(id) => client.copy(id),
                     UIEvent::Remove(id) => client.remove(id),
+                    UIEvent::Clear => client.clear(),
                 }
             }
         });
@@ -10,12 +10,12 @@ use self::image::ImageWidget;
 use self::label::LabelWidget;
 use self::slider::SliderWidget;
 use crate::channels::AsyncSenderExt;
-use crate::config::{CommonConfig, ModuleConfig};
+use crate::config::{CommonConfig, EventContext, ModuleConfig};
 use crate::modules::custom::button::ButtonWidget;
 use crate::modules::custom::progress::ProgressWidget;
 use crate::modules::{
-    AnyModuleFactory, BarModuleFactory, Module, ModuleInfo, ModuleParts, ModulePopup,
-    ModuleUpdateEvent, PopupButton, PopupModuleFactory, WidgetContext, add_events,
+    AnyModuleFactory, BarModuleFactory, Module, ModuleFactory, ModuleInfo, ModuleParts,
+    ModulePopup, ModuleUpdateEvent, PopupButton, PopupModuleFactory, WidgetContext, add_events,
 };
 use crate::script::Script;
 use crate::{module_impl, spawn};
@@ -28,7 +28,7 @@ use std::rc::Rc;
 use tokio::sync::mpsc;
 use tracing::{debug, error};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub struct CustomModule {
     /// Modules and widgets to add to the bar container.
@@ -46,7 +46,7 @@ pub struct CustomModule {
     pub common: Option<CommonConfig>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub struct WidgetConfig {
     /// One of a custom module native Ironbar module.
@@ -58,7 +58,7 @@ pub struct WidgetConfig {
     common: CommonConfig,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum WidgetOrModule {
@@ -69,7 +69,7 @@ pub enum WidgetOrModule {
     Module(ModuleConfig),
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum Widget {
@@ -154,12 +154,19 @@ impl WidgetOrModule {
 impl Widget {
     /// Creates this widget and adds it to the parent container
     fn add_to(self, parent: &gtk::Box, context: &CustomWidgetContext, common: CommonConfig) {
+        let event_context = EventContext {
+            module_name: common.name.clone().unwrap_or_else(|| "custom".to_string()),
+            bar_name: context.module_factory.bar().name().to_string(),
+            monitor: context.info.output_name.to_string(),
+        };
+
         macro_rules! create {
             ($widget:expr) => {
                 add_events(
                     &$widget.into_widget(context.clone()),
                     common,
                     context.bar_orientation,
+                    &event_context,
                 )
             };
         }
@@ -9,10 +9,10 @@ use crate::channels::{AsyncSenderExt, MpscReceiverExt};
 use crate::config::ModuleOrientation;
 use crate::dynamic_value::dynamic_string;
 use crate::modules::custom::set_length;
-use crate::script::{OutputStream, Script, ScriptInput};
+use crate::script::{JsonOutput, OutputFormat, OutputStream, Script, ScriptInput};
 use crate::{build, spawn};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct ProgressWidget {
@@ -46,6 +46,10 @@ pub struct ProgressWidget {
     ///
     /// Note that this expects a numeric value between `0`-`max` as output.
     ///
+    /// If the script uses the `json` format,
+    /// the `percentage` field is used as the value instead,
+    /// and `text` is shown if no `label` is set.
+    ///
     /// **Default**: `null`
     value: Option<ScriptInput>,
 
@@ -89,6 +93,8 @@ impl CustomWidget for ProgressWidget {
 
         if let Some(value) = self.value {
             let script = Script::from(value);
+            let format = script.format;
+            let show_text = self.label.is_none();
             let progress = progress.clone();
 
             let (tx, rx) = mpsc::channel(128);
@@ -96,16 +102,35 @@ impl CustomWidget for ProgressWidget {
             spawn(async move {
                 script
                     .run(None, move |stream, _success| match stream {
-                        OutputStream::Stdout(out) => match out.parse::<f64>() {
-                            Ok(value) => tx.send_spawn(value),
-                            Err(err) => error!("{err:?}"),
-                        },
+                        OutputStream::Stdout(out) => tx.send_spawn(out),
                         OutputStream::Stderr(err) => error!("{err:?}"),
                     })
                     .await;
             });
 
-            rx.recv_glib((), move |(), value| progress.set_fraction(value / self.max));
+            let mut previous: Option<JsonOutput> = None;
+
+            rx.recv_glib((), move |(), out: String| match format {
+                OutputFormat::Text => match out.parse::<f64>() {
+                    Ok(value) => progress.set_fraction(value / self.max),
+                    Err(err) => error!("{err:?}"),
+                },
+                OutputFormat::Json => {
+                    let output = JsonOutput::parse(&out);
+
+                    if let Some(percentage) = output.percentage {
+                        progress.set_fraction(percentage / 100.0);
+                    }
+
+                    if show_text {
+                        progress.set_show_text(!output.text.is_empty());
+                        progress.set_text(Some(&output.text));
+                    }
+
+                    output.apply(&progress, previous.as_ref());
+                    previous = Some(output);
+                }
+            });
         }
 
         if let Some(text) = self.label {
@@ -12,10 +12,10 @@ use super::{CustomWidget, CustomWidgetContext, ExecEvent};
 use crate::channels::{AsyncSenderExt, MpscReceiverExt};
 use crate::config::ModuleOrientation;
 use crate::modules::custom::set_length;
-use crate::script::{OutputStream, Script, ScriptInput};
+use crate::script::{JsonOutput, OutputFormat, OutputStream, Script, ScriptInput};
 use crate::{build, spawn};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct SliderWidget {
@@ -40,6 +40,9 @@ pub struct SliderWidget {
     /// Script to run to get the slider value.
     /// Output must be a valid number.
     ///
+    /// If the script uses the `json` format,
+    /// the `percentage` field is used to set the value between `min` and `max`.
+    ///
     /// **Default**: `null`
     value: Option<ScriptInput>,
 
@@ -162,6 +165,8 @@ impl CustomWidget for SliderWidget {
 
         if let Some(value) = self.value {
             let script = Script::from(value);
+            let format = script.format;
+            let (min, max) = (self.min, self.max);
             let scale = scale.clone();
 
             let (tx, rx) = mpsc::channel(128);
@@ -169,16 +174,30 @@ impl CustomWidget for SliderWidget {
             spawn(async move {
                 script
                     .run(None, move |stream, _success| match stream {
-                        OutputStream::Stdout(out) => match out.parse() {
-                            Ok(value) => tx.send_spawn(value),
-                            Err(err) => error!("{err:?}"),
-                        },
+                        OutputStream::Stdout(out) => tx.send_spawn(out),
                         OutputStream::Stderr(err) => error!("{err:?}"),
                     })
                     .await;
             });
 
-            rx.recv_glib((), move |(), value| scale.set_value(value));
+            let mut previous: Option<JsonOutput> = None;
+
+            rx.recv_glib((), move |(), out: String| match format {
+                OutputFormat::Text => match out.parse() {
+                    Ok(value) => scale.set_value(value),
+                    Err(err) => error!("{err:?}"),
+                },
+                OutputFormat::Json => {
+                    let output = JsonOutput::parse(&out);
+
+                    if let Some(percentage) = output.percentage {
+                        scale.set_value(min + (max - min) * percentage / 100.0);
+                    }
+
+                    output.apply(&scale, previous.as_ref());
+                    previous = Some(output);
+                }
+            });
         }
 
         scale
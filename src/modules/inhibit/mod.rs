@@ -9,7 +9,7 @@ use tracing::{debug, trace};
 use crate::channels::{AsyncSenderExt, BroadcastReceiverExt};
 use crate::clients::inhibit;
 use crate::gtk_helpers::{IronbarGtkExt, IronbarLabelExt, MouseButton};
-use crate::modules::{Module, ModuleInfo, ModuleParts, WidgetContext};
+use crate::modules::{Module, ModuleInfo, ModuleParts, WidgetContext, unknown_action};
 use crate::{module_impl, spawn};
 
 mod config;
@@ -41,6 +41,14 @@ impl Module<Button> for InhibitModule {
 
     module_impl!("inhibit");
 
+    fn parse_action(action: &str, _args: &[String]) -> Result<Self::ReceiveMessage> {
+        match action {
+            "toggle" => Ok(InhibitCommand::Toggle),
+            "cycle" => Ok(InhibitCommand::Cycle),
+            _ => Err(unknown_action(action, &["toggle", "cycle"])),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
@@ -6,6 +6,7 @@ use self::item::{AppearanceOptions, Item, ItemButton, Window};
 use self::open_state::OpenState;
 use super::{
     Module, ModuleInfo, ModuleParts, ModulePopup, ModuleUpdateEvent, PopupButton, WidgetContext,
+    action_arg, unknown_action,
 };
 use crate::channels::{AsyncSenderExt, BroadcastReceiverExt};
 use crate::clients::wayland::{self, ToplevelEvent};
@@ -25,7 +26,7 @@ use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{debug, error, trace, warn};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct LauncherModule {
@@ -138,7 +139,7 @@ impl Default for LauncherModule {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 struct Icons {
@@ -204,6 +205,15 @@ impl Module<gtk::Box> for LauncherModule {
 
     module_impl!("launcher");
 
+    fn parse_action(action: &str, args: &[String]) -> crate::Result<Self::ReceiveMessage> {
+        match action {
+            "focus" => action_arg(args, 0, "app_id").map(ItemEvent::FocusItem),
+            "open" => action_arg(args, 0, "app_id").map(ItemEvent::OpenItem),
+            "minimize" => action_arg(args, 0, "app_id").map(ItemEvent::MinimizeItem),
+            _ => Err(unknown_action(action, &["focus", "open", "minimize"])),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
@@ -32,7 +32,7 @@ pub struct XdgSection {
     pub applications: IndexMap<String, MenuApplication>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 pub struct MenuApplication {
     pub label: String,
     pub file_name: String,
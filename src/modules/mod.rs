@@ -1,19 +1,23 @@
-use std::fmt::{Debug, Formatter};
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Formatter};
 use std::rc::Rc;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::Ironbar;
 use crate::bar::Bar;
 use crate::channels::{MpscReceiverExt, SyncSenderExt};
 use crate::clients::{ClientResult, ProvidesClient, ProvidesFallibleClient};
-use crate::config::{BarPosition, CommonConfig, TransitionType};
+use crate::config::{BarPosition, CommonConfig, EventContext, TransitionType};
 use crate::gtk_helpers::IronbarGtkExt;
 use crate::popup::{ButtonFinder, Popup};
-use color_eyre::Result;
+use color_eyre::{Report, Result};
 use gtk::gdk::Monitor;
 use gtk::prelude::*;
 use gtk::{Application, Button, Orientation, Revealer, Widget};
+use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, mpsc};
+use tokio::task::AbortHandle;
 use tracing::{debug, trace};
 
 #[cfg(feature = "battery")]
@@ -65,10 +69,17 @@ pub mod volume;
 #[cfg(feature = "workspaces")]
 pub mod workspaces;
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ipc", derive(clap::ValueEnum))]
 pub enum ModuleLocation {
+    #[serde(rename = "start")]
+    #[cfg_attr(feature = "ipc", value(name = "start"))]
     Left,
+    #[serde(rename = "center")]
+    #[cfg_attr(feature = "ipc", value(name = "center"))]
     Center,
+    #[serde(rename = "end")]
+    #[cfg_attr(feature = "ipc", value(name = "end"))]
     Right,
 }
 
@@ -153,8 +164,135 @@ where
 pub struct ModuleRef {
     pub id: usize,
     pub name: String,
+    /// The module type, as used for the `type` config key.
+    pub module_type: &'static str,
+    pub location: ModuleLocation,
     pub root_widget: Widget,
     pub popup: Option<ModulePopupParts>,
+    pub actions: ModuleActions,
+}
+
+thread_local! {
+    /// Handles for tasks spawned while a module is being created.
+    static CREATING_TASKS: RefCell<Option<Vec<AbortHandle>>> = const { RefCell::new(None) };
+}
+
+/// The Tokio tasks spawned while creating a module.
+///
+/// The tasks are aborted when the module is unloaded,
+/// or when this is dropped.
+#[derive(Debug, Default)]
+pub struct ModuleTasks(Vec<AbortHandle>);
+
+impl ModuleTasks {
+    /// Runs `f`, collecting every task spawned on this thread while it runs.
+    pub fn collect<T>(f: impl FnOnce() -> T) -> (T, Self) {
+        let previous = CREATING_TASKS.with_borrow_mut(|tasks| tasks.replace(Vec::new()));
+        let res = f();
+        let tasks = CREATING_TASKS.with_borrow_mut(|tasks| std::mem::replace(tasks, previous));
+
+        (res, Self(tasks.unwrap_or_default()))
+    }
+
+    /// Runs `f` without collecting the tasks it spawns.
+    ///
+    /// Used for shared clients, which outlive the module which first requested them.
+    pub fn untracked<T>(f: impl FnOnce() -> T) -> T {
+        let previous = CREATING_TASKS.with_borrow_mut(Option::take);
+        let res = f();
+        CREATING_TASKS.with_borrow_mut(|tasks| *tasks = previous);
+
+        res
+    }
+
+    /// Records a newly spawned task,
+    /// if a module is being created on this thread.
+    pub fn track(handle: AbortHandle) {
+        CREATING_TASKS.with_borrow_mut(|tasks| {
+            if let Some(tasks) = tasks {
+                tasks.push(handle);
+            }
+        });
+    }
+
+    /// Aborts all the module's tasks.
+    pub fn abort(&self) {
+        for task in &self.0 {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for ModuleTasks {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+type ActionHandler = dyn Fn(&str, &[String]) -> Result<()>;
+
+/// Sends actions received over IPC to a module's controller.
+#[derive(Clone)]
+pub struct ModuleActions {
+    send: Rc<ActionHandler>,
+    check: Rc<ActionHandler>,
+}
+
+impl ModuleActions {
+    fn new<TModule, TWidget>(controller_tx: mpsc::Sender<TModule::ReceiveMessage>) -> Self
+    where
+        TModule: Module<TWidget> + 'static,
+        TWidget: IsA<Widget>,
+        TModule::ReceiveMessage: 'static,
+    {
+        Self {
+            send: Rc::new(move |action, args| {
+                let message = TModule::parse_action(action, args)?;
+                controller_tx
+                    .try_send(message)
+                    .map_err(|err| Report::msg(format!("failed to send action: {err}")))
+            }),
+            check: Rc::new(|action, args| TModule::parse_action(action, args).map(|_| ())),
+        }
+    }
+
+    /// Parses and sends an action to the module.
+    pub fn send(&self, action: &str, args: &[String]) -> Result<()> {
+        (self.send)(action, args)
+    }
+
+    /// Parses an action, without sending it to the module.
+    pub fn check(&self, action: &str, args: &[String]) -> Result<()> {
+        (self.check)(action, args)
+    }
+}
+
+impl Debug for ModuleActions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ModuleActions")
+    }
+}
+
+/// Creates the error for an action which a module does not support.
+pub fn unknown_action(action: &str, actions: &[&str]) -> Report {
+    Report::msg(format!(
+        "unknown action `{action}`, expected one of: {}",
+        actions.join(", ")
+    ))
+}
+
+/// Gets and parses the action argument at `index`.
+pub fn action_arg<T>(args: &[String], index: usize, name: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let arg = args
+        .get(index)
+        .ok_or_else(|| Report::msg(format!("missing argument `{name}`")))?;
+
+    arg.parse()
+        .map_err(|err| Report::msg(format!("invalid argument `{name}`: {err}")))
 }
 
 pub struct ModuleParts<W: IsA<Widget>> {
@@ -315,6 +453,14 @@ where
     }
 
     fn take_common(&mut self) -> CommonConfig;
+
+    /// Parses an action sent to the module over IPC
+    /// into a message for its controller.
+    ///
+    /// By default, modules do not support any actions.
+    fn parse_action(action: &str, _args: &[String]) -> Result<Self::ReceiveMessage> {
+        Err(unknown_action(action, &[]))
+    }
 }
 
 pub trait ModuleFactory {
@@ -325,9 +471,10 @@ pub trait ModuleFactory {
         info: &ModuleInfo,
     ) -> Result<ModuleRef>
     where
-        TModule: Module<TWidget, SendMessage = TSend, ReceiveMessage = TRev>,
+        TModule: Module<TWidget, SendMessage = TSend, ReceiveMessage = TRev> + 'static,
         TWidget: IsA<Widget>,
         TSend: Debug + Clone + Send + 'static,
+        TRev: 'static,
     {
         let id = Ironbar::unique_id();
         let common = module.take_common();
@@ -353,6 +500,8 @@ pub trait ModuleFactory {
 
         module.spawn_controller(info, &context, controller_rx)?;
 
+        let actions = ModuleActions::new::<TModule, TWidget>(context.controller_tx.clone());
+
         let module_name = TModule::name();
         let instance_name = common
             .name
@@ -370,23 +519,34 @@ pub trait ModuleFactory {
                 .container
                 .add_css_class(&format!("popup-{module_name}"));
 
-            self.popup().register_content(id, popup_content);
+            self.popup()
+                .register_content(id, instance_name.clone(), popup_content);
         }
 
         self.setup_receiver(tx, ui_rx, module_name, id, common.disable_popup);
 
+        let event_context = EventContext {
+            module_name: instance_name.clone(),
+            bar_name: self.bar().name().to_string(),
+            monitor: info.output_name.to_string(),
+        };
+
         let revealer = add_events(
             &module_parts.widget,
             common,
             info.bar_position.orientation(),
+            &event_context,
         );
         container.append(&revealer);
 
         Ok(ModuleRef {
             id,
             name: instance_name,
+            module_type: module_name,
+            location: info.location.clone(),
             root_widget: module_parts.widget.upcast(),
             popup: module_parts.popup,
+            actions,
         })
     }
 
@@ -617,6 +777,7 @@ pub fn add_events<W: IsA<Widget>>(
     widget: &W,
     common: CommonConfig,
     orientation: Orientation,
+    context: &EventContext,
 ) -> Revealer {
     let transition_type = common
         .transition_type
@@ -632,6 +793,6 @@ pub fn add_events<W: IsA<Widget>>(
     revealer.set_child(Some(widget));
     revealer.set_reveal_child(true);
 
-    common.install_events(widget, &revealer);
+    common.install_events(widget, &revealer, context);
     revealer
 }
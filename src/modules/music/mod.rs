@@ -5,7 +5,7 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-use color_eyre::Result;
+use color_eyre::{Report, Result};
 use glib::Propagation;
 use gtk::gdk::Paintable;
 use gtk::prelude::*;
@@ -24,7 +24,8 @@ use crate::gtk_helpers::{IronbarLabelExt, OverflowLabel};
 use crate::image::{IconButton, IconLabel, IconPrefixedLabel};
 use crate::modules::PopupButton;
 use crate::modules::{
-    Module, ModuleInfo, ModuleParts, ModulePopup, ModuleUpdateEvent, WidgetContext,
+    Module, ModuleInfo, ModuleParts, ModulePopup, ModuleTasks, ModuleUpdateEvent, WidgetContext,
+    action_arg, unknown_action,
 };
 use crate::{module_impl, spawn};
 
@@ -35,6 +36,8 @@ pub enum PlayerCommand {
     Previous,
     Play,
     Pause,
+    /// Pauses if playing, otherwise plays.
+    PlayPause,
     Next,
     Volume(u8),
     Seek(Duration),
@@ -90,6 +93,35 @@ impl Module<Button> for MusicModule {
 
     module_impl!("music");
 
+    fn parse_action(action: &str, args: &[String]) -> Result<Self::ReceiveMessage> {
+        match action {
+            "previous" => Ok(PlayerCommand::Previous),
+            "play" => Ok(PlayerCommand::Play),
+            "pause" => Ok(PlayerCommand::Pause),
+            "play_pause" => Ok(PlayerCommand::PlayPause),
+            "next" => Ok(PlayerCommand::Next),
+            "volume" => action_arg(args, 0, "percent").map(PlayerCommand::Volume),
+            "seek" => {
+                let secs = action_arg(args, 0, "seconds")?;
+                Duration::try_from_secs_f64(secs)
+                    .map(PlayerCommand::Seek)
+                    .map_err(|err| Report::msg(format!("invalid argument `seconds`: {err}")))
+            }
+            _ => Err(unknown_action(
+                action,
+                &[
+                    "previous",
+                    "play",
+                    "pause",
+                    "play_pause",
+                    "next",
+                    "volume",
+                    "seek",
+                ],
+            )),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
@@ -98,17 +130,22 @@ impl Module<Button> for MusicModule {
     ) -> Result<()> {
         let format = self.format.clone();
 
-        let client = get_client(
-            context.ironbar.clients.borrow_mut(),
-            self.player_type,
-            self.host.clone(),
-            self.music_dir.clone(),
-        );
+        let client = ModuleTasks::untracked(|| {
+            get_client(
+                context.ironbar.clients.borrow_mut(),
+                self.player_type,
+                self.host.clone(),
+                self.music_dir.clone(),
+            )
+        });
+
+        let playing = Arc::new(AtomicBool::new(false));
 
         // receive player updates
         {
             let tx = context.tx.clone();
             let client = client.clone();
+            let playing = playing.clone();
 
             spawn(async move {
                 loop {
@@ -116,20 +153,28 @@ impl Module<Button> for MusicModule {
 
                     while let Ok(update) = rx.recv().await {
                         match update {
-                            PlayerUpdate::Update(track, status) => match *track {
-                                Some(track) => {
-                                    let display_string = replace_tokens(format.as_str(), &track);
-
-                                    let update = SongUpdate {
-                                        song: track,
-                                        status,
-                                        display_string,
-                                    };
-
-                                    tx.send_update(ControllerEvent::Update(Some(update))).await;
+                            PlayerUpdate::Update(track, status) => {
+                                playing.store(
+                                    matches!(status.state, PlayerState::Playing),
+                                    Ordering::Relaxed,
+                                );
+
+                                match *track {
+                                    Some(track) => {
+                                        let display_string =
+                                            replace_tokens(format.as_str(), &track);
+
+                                        let update = SongUpdate {
+                                            song: track,
+                                            status,
+                                            display_string,
+                                        };
+
+                                        tx.send_update(ControllerEvent::Update(Some(update))).await;
+                                    }
+                                    None => tx.send_update(ControllerEvent::Update(None)).await,
                                 }
-                                None => tx.send_update(ControllerEvent::Update(None)).await,
-                            },
+                            }
                             PlayerUpdate::ProgressTick(progress_tick) => {
                                 tx.send_update(ControllerEvent::UpdateProgress(progress_tick))
                                     .await;
@@ -148,6 +193,10 @@ impl Module<Button> for MusicModule {
                         PlayerCommand::Previous => client.prev(),
                         PlayerCommand::Play => client.play(),
                         PlayerCommand::Pause => client.pause(),
+                        PlayerCommand::PlayPause if playing.load(Ordering::Relaxed) => {
+                            client.pause()
+                        }
+                        PlayerCommand::PlayPause => client.play(),
                         PlayerCommand::Next => client.next(),
                         PlayerCommand::Volume(vol) => client.set_volume_percent(vol),
                         PlayerCommand::Seek(duration) => client.seek(duration),
@@ -1,16 +1,21 @@
 use crate::channels::{AsyncSenderExt, BroadcastReceiverExt};
 use crate::config::{CommonConfig, LayoutConfig};
-use crate::gtk_helpers::IronbarLabelExt;
-use crate::modules::{Module, ModuleInfo, ModuleParts, WidgetContext};
-use crate::script::{OutputStream, Script, ScriptMode};
-use crate::{module_impl, spawn};
+use crate::gtk_helpers::{IronbarGtkExt, IronbarLabelExt, MouseButton};
+use crate::modules::{Module, ModuleInfo, ModuleParts, WidgetContext, unknown_action};
+use crate::script::{JsonOutput, OutputFormat, OutputStream, Script, ScriptMode};
+use crate::{Ironbar, module_impl, spawn};
 use color_eyre::{Help, Report, Result};
+use glib::GString;
 use gtk::Label;
+use gtk::prelude::*;
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tokio::signal::unix::{self, SignalKind};
 use tokio::sync::mpsc;
-use tracing::error;
+use tracing::{debug, error};
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct ScriptModule {
@@ -35,6 +40,67 @@ pub struct ScriptModule {
     /// **Default**: `5000`
     interval: u64,
 
+    /// Additional environment variables to set for the script.
+    ///
+    /// **Default**: `{}`
+    env: BTreeMap<String, String>,
+
+    /// The working directory to run the script in.
+    ///
+    /// **Default**: `null` (Ironbar's working directory)
+    cwd: Option<PathBuf>,
+
+    /// The shell used to run `cmd`.
+    /// The command is passed to the shell using `-c`.
+    ///
+    /// **Default**: `/bin/sh`
+    shell: String,
+
+    /// A program and its arguments to run directly, without a shell.
+    /// When set, `cmd` and `shell` are ignored.
+    ///
+    /// **Default**: `[]`
+    argv: Vec<String>,
+
+    /// The maximum time in milliseconds a single run may take
+    /// before the script is killed.
+    /// Has no effect in `watch` mode.
+    ///
+    /// **Default**: `null`
+    timeout: Option<u64>,
+
+    /// How the script output is read.
+    /// See [output formats](#output-formats) for more info.
+    ///
+    /// **Valid options**: `text`, `json`
+    /// <br />
+    /// **Default**: `text`
+    format: OutputFormat,
+
+    /// The maximum number of times in a row the script can fail
+    /// before it is no longer restarted.
+    ///
+    /// **Default**: `null` (always restart)
+    max_restarts: Option<u32>,
+
+    /// The maximum time in milliseconds to wait before restarting a failing script.
+    /// The wait doubles after each consecutive failure, up to this value.
+    ///
+    /// **Default**: `60000`
+    max_backoff: u64,
+
+    /// A realtime signal offset which forces the script to run again when received.
+    /// For example, a value of `5` allows the script to be refreshed with `pkill -RTMIN+5 ironbar`.
+    ///
+    /// **Default**: `null`
+    signal: Option<u8>,
+
+    /// Whether to run the script again when the module is left-clicked.
+    /// This runs alongside any `on_click_left` script.
+    ///
+    /// **Default**: `false`
+    refresh_on_click: bool,
+
     // -- Common --
     /// See [layout options](module-level-options#layout)
     #[serde(flatten)]
@@ -51,6 +117,16 @@ impl Default for ScriptModule {
             cmd: String::new(),
             mode: ScriptMode::Poll,
             interval: 5000,
+            env: BTreeMap::new(),
+            cwd: None,
+            shell: String::from("/bin/sh"),
+            argv: vec![],
+            timeout: None,
+            format: OutputFormat::default(),
+            max_restarts: None,
+            max_backoff: 60_000,
+            signal: None,
+            refresh_on_click: false,
             layout: LayoutConfig::default(),
             common: Some(CommonConfig::default()),
         }
@@ -63,31 +139,110 @@ impl From<&ScriptModule> for Script {
             mode: module.mode,
             cmd: module.cmd.clone(),
             interval: module.interval,
+            env: module.env.clone(),
+            cwd: module.cwd.clone(),
+            shell: module.shell.clone(),
+            argv: module.argv.clone(),
+            timeout: module.timeout,
+            format: module.format,
+            max_restarts: module.max_restarts,
+            max_backoff: module.max_backoff,
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum ScriptUpdate {
+    /// The script produced output.
+    Output(String),
+    /// The script failed, with the error message.
+    Error(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ScriptCommand {
+    /// Runs the script again immediately.
+    Refresh,
+}
+
 impl Module<Label> for ScriptModule {
-    type SendMessage = String;
-    type ReceiveMessage = ();
+    type SendMessage = ScriptUpdate;
+    type ReceiveMessage = ScriptCommand;
 
     module_impl!("script");
 
+    fn parse_action(action: &str, _args: &[String]) -> Result<Self::ReceiveMessage> {
+        match action {
+            "refresh" => Ok(ScriptCommand::Refresh),
+            _ => Err(unknown_action(action, &["refresh"])),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
         context: &WidgetContext<Self::SendMessage, Self::ReceiveMessage>,
-        _rx: mpsc::Receiver<Self::ReceiveMessage>,
+        mut rx: mpsc::Receiver<Self::ReceiveMessage>,
     ) -> Result<()> {
+        let signal = match self.signal {
+            Some(offset) => {
+                let signal = libc::SIGRTMIN() + i32::from(offset);
+                if signal > libc::SIGRTMAX() {
+                    return Err(Report::msg(format!(
+                        "Signal offset {offset} is out of range"
+                    )));
+                }
+
+                Some((offset, signal))
+            }
+            None => None,
+        };
+
         let script: Script = self.into();
 
+        {
+            let script = script.clone();
+            spawn(async move {
+                while let Some(ScriptCommand::Refresh) = rx.recv().await {
+                    debug!("Refreshing script '{}'", script.cmd);
+                    Ironbar::script_registry().refresh(&script);
+                }
+            });
+        }
+
+        if let Some((offset, signal)) = signal {
+            let script = script.clone();
+
+            spawn(async move {
+                let mut stream = match unix::signal(SignalKind::from_raw(signal)) {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        error!("Failed to listen for signal RTMIN+{offset}: {err:?}");
+                        return;
+                    }
+                };
+
+                while stream.recv().await.is_some() {
+                    debug!(
+                        "Received RTMIN+{offset}, refreshing script '{}'",
+                        script.cmd
+                    );
+                    Ironbar::script_registry().refresh(&script);
+                }
+            });
+        }
+
         let tx = context.tx.clone();
         spawn(async move {
-            script.run(None, move |out, _| match out {
+            script.run(None, move |out, success| match out {
                OutputStream::Stdout(stdout) => {
-                   tx.send_update_spawn(stdout);
+                   tx.send_update_spawn(ScriptUpdate::Output(stdout));
                },
                OutputStream::Stderr(stderr) => {
+                   if !success {
+                       tx.send_update_spawn(ScriptUpdate::Error(stderr.clone()));
+                   }
+
                    error!("{:?}", Report::msg(stderr)
                                     .wrap_err("Watched script error:")
                                     .suggestion("Check the path to your script")
@@ -110,9 +265,50 @@ impl Module<Label> for ScriptModule {
             .justify(self.layout.justify.into())
             .build();
 
+        if self.refresh_on_click {
+            let tx = context.controller_tx.clone();
+            label.connect_pressed(MouseButton::Primary, move || {
+                tx.send_spawn(ScriptCommand::Refresh);
+            });
+        }
+
+        let mut previous: Option<JsonOutput> = None;
+        let format = self.format;
+
+        // the tooltip to restore once the script recovers,
+        // set while the script is failing
+        let mut tooltip: Option<Option<GString>> = None;
+
         context
             .subscribe()
-            .recv_glib(&label, |label, s| label.set_label_escaped(&s));
+            .recv_glib(&label, move |label, update| match update {
+                ScriptUpdate::Output(s) => {
+                    if let Some(tooltip) = tooltip.take() {
+                        label.remove_css_class("error");
+                        label.set_tooltip_text(tooltip.as_ref().map(GString::as_str));
+                    }
+
+                    match format {
+                        OutputFormat::Text => label.set_label_escaped(&s),
+                        OutputFormat::Json => {
+                            let output = JsonOutput::parse(&s);
+
+                            label.set_label_escaped(&output.text);
+                            output.apply(label, previous.as_ref());
+
+                            previous = Some(output);
+                        }
+                    }
+                }
+                ScriptUpdate::Error(err) => {
+                    if tooltip.is_none() {
+                        tooltip = Some(label.tooltip_text());
+                        label.add_css_class("error");
+                    }
+
+                    label.set_tooltip_text(Some(&err));
+                }
+            });
 
         Ok(ModuleParts {
             widget: label,
@@ -4,6 +4,7 @@ use crate::config::{CommonConfig, LayoutConfig, MarqueeMode, TruncateMode};
 use crate::gtk_helpers::{IronbarLabelExt, OverflowLabel};
 use crate::modules::{
     Module, ModuleInfo, ModuleParts, ModulePopup, ModuleUpdateEvent, PopupButton, WidgetContext,
+    action_arg, unknown_action,
 };
 use crate::{lock, module_impl, spawn};
 use glib::subclass::prelude::*;
@@ -17,9 +18,9 @@ use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use tokio::sync::mpsc;
-use tracing::trace;
+use tracing::{trace, warn};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct VolumeModule {
@@ -73,7 +74,7 @@ impl Default for VolumeModule {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct Icons {
@@ -127,6 +128,12 @@ pub enum Update {
 
     InputVolume(u32, f64),
     InputMute(u32, bool),
+
+    /// Sets the volume of the active sink.
+    ActiveSinkVolume(f64),
+    /// Sets the mute state of the active sink,
+    /// or toggles it if not set.
+    ActiveSinkMute(Option<bool>),
 }
 
 glib::wrapper! {
@@ -160,12 +167,37 @@ impl ObjectSubclass for DropdownItemData {
     type Type = DropdownItem;
 }
 
+/// Gets the sink currently shown on the bar.
+fn active_sink(client: &volume::Client) -> Option<volume::Sink> {
+    let sinks = client.sinks();
+    let sink = lock!(sinks).iter().find(|sink| sink.active).cloned();
+
+    if sink.is_none() {
+        warn!("No active sink");
+    }
+
+    sink
+}
+
 impl Module<Button> for VolumeModule {
     type SendMessage = Event;
     type ReceiveMessage = Update;
 
     module_impl!("volume");
 
+    fn parse_action(action: &str, args: &[String]) -> color_eyre::Result<Self::ReceiveMessage> {
+        match action {
+            "set" => action_arg(args, 0, "percent").map(Update::ActiveSinkVolume),
+            "mute" => Ok(Update::ActiveSinkMute(Some(true))),
+            "unmute" => Ok(Update::ActiveSinkMute(Some(false))),
+            "toggle_mute" => Ok(Update::ActiveSinkMute(None)),
+            _ => Err(unknown_action(
+                action,
+                &["set", "mute", "unmute", "toggle_mute"],
+            )),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
@@ -225,6 +257,16 @@ impl Module<Button> for VolumeModule {
                     Update::SinkMute(name, muted) => client.set_sink_muted(&name, muted),
                     Update::InputVolume(index, volume) => client.set_input_volume(index, volume),
                     Update::InputMute(index, muted) => client.set_input_muted(index, muted),
+                    Update::ActiveSinkVolume(volume) => {
+                        if let Some(sink) = active_sink(&client) {
+                            client.set_sink_volume(&sink.name, volume);
+                        }
+                    }
+                    Update::ActiveSinkMute(muted) => {
+                        if let Some(sink) = active_sink(&client) {
+                            client.set_sink_muted(&sink.name, muted.unwrap_or(!sink.muted));
+                        }
+                    }
                 }
             }
         });
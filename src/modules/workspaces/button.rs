@@ -1,7 +1,7 @@
 use super::open_state::OpenState;
 use crate::channels::AsyncSenderExt;
 use crate::image::IconButton;
-use crate::modules::workspaces::WorkspaceItemContext;
+use crate::modules::workspaces::{WorkspaceCommand, WorkspaceItemContext};
 use glib::signal::SignalHandlerId;
 use gtk::Button as GtkButton;
 use gtk::prelude::*;
@@ -12,7 +12,7 @@ pub struct Button {
     button: IconButton,
     workspace_id: i64,
     conn_id: Option<SignalHandlerId>,
-    tx: mpsc::Sender<i64>,
+    tx: mpsc::Sender<WorkspaceCommand>,
 }
 
 impl Button {
@@ -32,7 +32,7 @@ impl Button {
         let tx = context.tx.clone();
 
         let conn_id = button.connect_clicked(move |_item| {
-            tx.send_spawn(id);
+            tx.send_spawn(WorkspaceCommand::Focus(id));
         });
 
         let btn = Self {
@@ -93,7 +93,7 @@ impl Button {
         }
         let tx = self.tx.clone();
         let conn_id = self.button.connect_clicked(move |_item| {
-            tx.send_spawn(id);
+            tx.send_spawn(WorkspaceCommand::Focus(id));
         });
         self.conn_id = Some(conn_id);
     }
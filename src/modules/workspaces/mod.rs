@@ -9,8 +9,10 @@ use crate::config::{CommonConfig, LayoutConfig, default};
 use crate::gtk_helpers::IronbarGtkExt;
 use crate::modules::workspaces::button_map::{ButtonMap, Identifier};
 use crate::modules::workspaces::open_state::OpenState;
-use crate::modules::{Module, ModuleInfo, ModuleParts, WidgetContext};
-use crate::{image, module_impl, spawn};
+use crate::modules::{
+    Module, ModuleInfo, ModuleParts, ModuleTasks, WidgetContext, action_arg, unknown_action,
+};
+use crate::{arc_mut, image, lock, module_impl, spawn};
 use color_eyre::{Report, Result};
 use gtk::prelude::*;
 use serde::Deserialize;
@@ -47,7 +49,7 @@ pub enum SortOrder {
     Index,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum Favorites {
@@ -61,7 +63,7 @@ impl Default for Favorites {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[serde(untagged)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 pub enum Format {
@@ -89,7 +91,7 @@ impl Format {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 #[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct WorkspacesModule {
@@ -204,7 +206,7 @@ pub struct WorkspaceItemContext {
     name_map: HashMap<String, String>,
     icon_size: i32,
     image_provider: image::Provider,
-    tx: mpsc::Sender<i64>,
+    tx: mpsc::Sender<WorkspaceCommand>,
     format_named: String,
     format_unnamed: String,
 }
@@ -276,12 +278,53 @@ fn reorder_workspaces(container: &gtk::Box, sort_order: SortOrder) {
     }
 }
 
+/// Keeps the map of workspace names to IDs in sync with an update.
+fn update_names(names: &mut HashMap<String, i64>, update: &WorkspaceUpdate) {
+    match update {
+        WorkspaceUpdate::Init(workspaces) => {
+            names.clear();
+            names.extend(
+                workspaces
+                    .iter()
+                    .map(|workspace| (workspace.name.clone(), workspace.id)),
+            );
+        }
+        WorkspaceUpdate::Add(workspace)
+        | WorkspaceUpdate::Move(workspace)
+        | WorkspaceUpdate::Focus { new: workspace, .. } => {
+            names.insert(workspace.name.clone(), workspace.id);
+        }
+        WorkspaceUpdate::Remove(id) => names.retain(|_, workspace_id| workspace_id != id),
+        WorkspaceUpdate::Rename { id, name } => {
+            names.retain(|_, workspace_id| workspace_id != id);
+            names.insert(name.clone(), *id);
+        }
+        WorkspaceUpdate::Urgent { .. } | WorkspaceUpdate::Unknown => {}
+    }
+}
+
+/// A request to change workspace.
+#[derive(Debug, Clone)]
+pub enum WorkspaceCommand {
+    /// Focuses the workspace with the given ID.
+    Focus(i64),
+    /// Focuses the workspace with the given name.
+    FocusName(String),
+}
+
 impl Module<gtk::Box> for WorkspacesModule {
     type SendMessage = WorkspaceUpdate;
-    type ReceiveMessage = i64;
+    type ReceiveMessage = WorkspaceCommand;
 
     module_impl!("workspaces");
 
+    fn parse_action(action: &str, args: &[String]) -> Result<Self::ReceiveMessage> {
+        match action {
+            "focus" => action_arg(args, 0, "name").map(WorkspaceCommand::FocusName),
+            _ => Err(unknown_action(action, &["focus"])),
+        }
+    }
+
     fn spawn_controller(
         &self,
         _info: &ModuleInfo,
@@ -289,18 +332,27 @@ impl Module<gtk::Box> for WorkspacesModule {
         mut rx: mpsc::Receiver<Self::ReceiveMessage>,
     ) -> Result<()> {
         let tx = context.tx.clone();
-        let client = context.ironbar.clients.borrow_mut().workspaces()?;
+        let client = ModuleTasks::untracked(|| context.ironbar.clients.borrow_mut().workspaces())?;
+
+        // workspace names to IDs, for focusing by name
+        let names = arc_mut!(HashMap::new());
+
         // Subscribe & send events
-        spawn(async move {
-            let mut srx = client.subscribe();
+        {
+            let names = names.clone();
 
-            trace!("Set up workspace subscription");
+            spawn(async move {
+                let mut srx = client.subscribe();
 
-            while let Ok(payload) = srx.recv().await {
-                debug!("Received update: {payload:?}");
-                tx.send_update(payload).await;
-            }
-        });
+                trace!("Set up workspace subscription");
+
+                while let Ok(payload) = srx.recv().await {
+                    debug!("Received update: {payload:?}");
+                    update_names(&mut *lock!(names), &payload);
+                    tx.send_update(payload).await;
+                }
+            });
+        }
 
         let client = context.try_client::<dyn WorkspaceClient>()?;
 
@@ -308,8 +360,21 @@ impl Module<gtk::Box> for WorkspacesModule {
         spawn(async move {
             trace!("Setting up UI event handler");
 
-            while let Some(id) = rx.recv().await {
-                client.focus(id);
+            while let Some(command) = rx.recv().await {
+                let id = match command {
+                    WorkspaceCommand::Focus(id) => Some(id),
+                    WorkspaceCommand::FocusName(name) => {
+                        let id = lock!(names).get(&name).copied();
+                        if id.is_none() {
+                            warn!("No workspace named '{name}'");
+                        }
+                        id
+                    }
+                };
+
+                if let Some(id) = id {
+                    client.focus(id);
+                }
             }
 
             Ok::<(), Report>(())
@@ -585,4 +650,38 @@ mod tests {
             ("{name}".to_string(), "{label}".to_string())
         );
     }
+
+    #[test]
+    fn test_update_names() {
+        use crate::clients::compositor::Visibility;
+
+        let workspace = |id: i64, name: &str| Workspace {
+            id,
+            index: id,
+            name: name.to_string(),
+            monitor: "DP-1".to_string(),
+            visibility: Visibility::Hidden,
+        };
+
+        let mut names = HashMap::new();
+
+        update_names(
+            &mut names,
+            &WorkspaceUpdate::Init(vec![workspace(1, "1"), workspace(2, "web")]),
+        );
+        update_names(&mut names, &WorkspaceUpdate::Add(workspace(3, "3")));
+        update_names(&mut names, &WorkspaceUpdate::Remove(1));
+        update_names(
+            &mut names,
+            &WorkspaceUpdate::Rename {
+                id: 2,
+                name: "mail".to_string(),
+            },
+        );
+
+        let mut names = names.into_iter().collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(names, [("3".to_string(), 3), ("mail".to_string(), 2)]);
+    }
 }
@@ -1,4 +1,6 @@
 use crate::config::BarPosition;
+#[cfg(feature = "ipc")]
+use crate::ipc::{Event, events};
 use crate::modules::{ModuleInfo, ModulePopupParts, PopupButton};
 use crate::rc_mut;
 use gtk::prelude::*;
@@ -11,7 +13,7 @@ use tracing::{debug, error};
 
 #[derive(Debug)]
 pub struct PopupCacheValue {
-    // pub name: String,
+    pub name: String,
     pub content: gtk::Box,
 }
 
@@ -30,6 +32,7 @@ pub struct Popup {
     pub button_cache: Rc<RefCell<Vec<Button>>>,
     pos: BarPosition,
     current_widget: Rc<RefCell<Option<CurrentWidgetInfo>>>,
+    bar_name: String,
 }
 
 impl Debug for Popup {
@@ -41,6 +44,7 @@ impl Debug for Popup {
             .field("button_cache", &self.button_cache)
             .field("pos", &self.pos)
             .field("current_widget", &self.current_widget)
+            .field("bar_name", &self.bar_name)
             .finish()
     }
 }
@@ -49,7 +53,7 @@ impl Popup {
     /// Creates a new popup window.
     /// This includes setting up gtk-layer-shell
     /// and an empty `gtk::Box` container.
-    pub fn new(module_info: &ModuleInfo, gap: i32, autohide: bool) -> Self {
+    pub fn new(bar_name: &str, module_info: &ModuleInfo, gap: i32, autohide: bool) -> Self {
         let pos = module_info.bar_position;
 
         let position = match pos {
@@ -77,17 +81,43 @@ impl Popup {
             popover.unparent();
         });
 
-        Self {
+        let popup = Self {
             popover,
             container_cache: rc_mut!(HashMap::new()),
             button_cache: rc_mut!(vec![]),
             button_finder_cache: rc_mut!(HashMap::new()),
             pos,
             current_widget: rc_mut!(None),
+            bar_name: bar_name.to_string(),
+        };
+
+        #[cfg(feature = "ipc")]
+        {
+            // clone fields individually to avoid a reference cycle on the popover
+            let bar_name = popup.bar_name.clone();
+            let container_cache = popup.container_cache.clone();
+            let current_widget = popup.current_widget.clone();
+
+            popup.popover.connect_closed(move |_| {
+                let widget_name = current_widget.borrow().and_then(|current| {
+                    container_cache
+                        .borrow()
+                        .get(&current.widget_id)
+                        .map(|value| value.name.clone())
+                });
+
+                events::emit(Event::Popup {
+                    bar_name: bar_name.clone(),
+                    widget_name,
+                    visible: false,
+                });
+            });
         }
+
+        popup
     }
 
-    pub fn register_content(&self, key: usize, content: ModulePopupParts) {
+    pub fn register_content(&self, key: usize, name: String, content: ModulePopupParts) {
         debug!("Registered popup content for #{}", key);
 
         for button in &content.buttons {
@@ -101,6 +131,7 @@ impl Popup {
         self.container_cache.borrow_mut().insert(
             key,
             PopupCacheValue {
+                name,
                 content: content.container.clone(),
             },
         );
@@ -112,6 +143,23 @@ impl Popup {
         }
     }
 
+    /// Removes the popup content registered for a widget,
+    /// hiding the popup if it is currently showing that content.
+    pub fn unregister_content(&self, key: usize, content: &ModulePopupParts) {
+        debug!("Unregistered popup content for #{}", key);
+
+        if self.current_widget() == Some(key) {
+            self.hide();
+        }
+
+        for button in &content.buttons {
+            self.unregister_button(button);
+        }
+
+        self.container_cache.borrow_mut().remove(&key);
+        self.button_finder_cache.borrow_mut().remove(&key);
+    }
+
     pub fn register_button(&self, button: Button) {
         button.ensure_popup_id();
         self.button_cache.borrow_mut().push(button);
@@ -128,10 +176,9 @@ impl Popup {
     pub fn show(&self, widget_id: usize, button_id: usize) {
         self.clear_window();
 
-        if let Some(PopupCacheValue { content, .. }) = self.container_cache.borrow().get(&widget_id)
+        if let Some(PopupCacheValue { name, content }) =
+            self.container_cache.borrow().get(&widget_id)
         {
-            *self.current_widget.borrow_mut() = Some(CurrentWidgetInfo { widget_id });
-
             let button = if let Some(finder) = self.button_finder_cache.borrow().get(&widget_id) {
                 finder(button_id)
             } else {
@@ -152,6 +199,9 @@ impl Popup {
             self.popover.unparent();
             self.popover.set_parent(&button);
             self.popover.popup();
+
+            *self.current_widget.borrow_mut() = Some(CurrentWidgetInfo { widget_id });
+            self.emit_opened(name);
         }
     }
 
@@ -163,16 +213,18 @@ impl Popup {
     pub fn show_for(&self, widget_id: usize, button: &Button) -> bool {
         self.clear_window();
 
-        if let Some(PopupCacheValue { content, .. }) = self.container_cache.borrow().get(&widget_id)
+        if let Some(PopupCacheValue { name, content }) =
+            self.container_cache.borrow().get(&widget_id)
         {
-            *self.current_widget.borrow_mut() = Some(CurrentWidgetInfo { widget_id });
-
             content.add_css_class("popup");
             self.popover.set_child(Some(content));
             self.popover.unparent();
             self.popover.set_parent(button);
             self.popover.popup();
 
+            *self.current_widget.borrow_mut() = Some(CurrentWidgetInfo { widget_id });
+            self.emit_opened(name);
+
             true
         } else {
             false
@@ -185,9 +237,9 @@ impl Popup {
 
     /// Hides the popup
     pub fn hide(&self) {
-        *self.current_widget.borrow_mut() = None;
         self.popover.popdown();
         self.popover.unparent();
+        *self.current_widget.borrow_mut() = None;
     }
 
     /// Checks if the popup is currently visible
@@ -198,4 +250,17 @@ impl Popup {
     pub fn current_widget(&self) -> Option<usize> {
         self.current_widget.borrow().map(|w| w.widget_id)
     }
+
+    /// Notifies IPC subscribers that the popup has opened.
+    #[cfg(feature = "ipc")]
+    fn emit_opened(&self, widget_name: &str) {
+        events::emit(Event::Popup {
+            bar_name: self.bar_name.clone(),
+            widget_name: Some(widget_name.to_string()),
+            visible: true,
+        });
+    }
+
+    #[cfg(not(feature = "ipc"))]
+    fn emit_opened(&self, _widget_name: &str) {}
 }
@@ -0,0 +1,118 @@
+use gtk::Widget;
+use gtk::prelude::*;
+use serde::{Deserialize, Deserializer};
+use tracing::warn;
+
+/// Structured script output,
+/// compatible with the JSON format used by Waybar's custom module.
+///
+/// ```json
+/// {"text": "42%", "tooltip": "Battery", "class": "warning", "percentage": 42, "alt": "charging"}
+/// ```
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct JsonOutput {
+    pub text: String,
+    pub tooltip: Option<String>,
+    #[serde(deserialize_with = "deserialize_class")]
+    pub class: Vec<String>,
+    pub percentage: Option<f64>,
+    pub alt: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Class {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+/// Accepts either a space-separated string or a list of classes.
+fn deserialize_class<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Class::deserialize(deserializer)? {
+        Class::Single(class) => class.split_whitespace().map(str::to_string).collect(),
+        Class::Multiple(classes) => classes,
+    })
+}
+
+impl JsonOutput {
+    /// Parses a line of script output.
+    ///
+    /// Output which is not valid JSON is used as the text as-is,
+    /// so that scripts can still print plain messages.
+    pub fn parse(line: &str) -> Self {
+        serde_json::from_str(line).unwrap_or_else(|err| {
+            warn!("Script output is not valid JSON: {err}");
+
+            Self {
+                text: line.to_string(),
+                ..Self::default()
+            }
+        })
+    }
+
+    /// Gets the CSS classes to apply,
+    /// including `alt` as an extra class.
+    fn classes(&self) -> impl Iterator<Item = &str> {
+        self.class
+            .iter()
+            .map(String::as_str)
+            .chain(self.alt.as_deref())
+    }
+
+    /// Applies the tooltip and CSS classes to `widget`,
+    /// removing any classes set by the `previous` output.
+    pub fn apply<W: IsA<Widget>>(&self, widget: &W, previous: Option<&Self>) {
+        if let Some(previous) = previous {
+            for class in previous.classes() {
+                widget.remove_css_class(class);
+            }
+        }
+
+        for class in self.classes() {
+            widget.add_css_class(class);
+        }
+
+        if self.tooltip.is_some() || previous.is_some_and(|previous| previous.tooltip.is_some()) {
+            widget.set_tooltip_text(self.tooltip.as_deref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full() {
+        let output = JsonOutput::parse(
+            r#"{"text": "42%", "tooltip": "Battery", "class": ["low", "charging"], "percentage": 42, "alt": "ac"}"#,
+        );
+
+        assert_eq!(output.text, "42%");
+        assert_eq!(output.tooltip.as_deref(), Some("Battery"));
+        assert_eq!(output.class, ["low", "charging"]);
+        assert_eq!(output.percentage, Some(42.0));
+        assert_eq!(
+            output.classes().collect::<Vec<_>>(),
+            ["low", "charging", "ac"]
+        );
+    }
+
+    #[test]
+    fn test_parse_class_string() {
+        let output = JsonOutput::parse(r#"{"text": "hello", "class": "a b"}"#);
+        assert_eq!(output.class, ["a", "b"]);
+        assert_eq!(output.tooltip, None);
+    }
+
+    #[test]
+    fn test_parse_plain_text() {
+        let output = JsonOutput::parse("hello");
+        assert_eq!(output.text, "hello");
+        assert!(output.class.is_empty());
+    }
+}
@@ -0,0 +1,727 @@
+#[cfg(any(feature = "script", feature = "custom"))]
+mod json;
+mod registry;
+
+#[cfg(any(feature = "script", feature = "custom"))]
+pub use self::json::JsonOutput;
+pub use self::registry::ScriptRegistry;
+
+use crate::{Ironbar, spawn};
+use color_eyre::eyre::WrapErr;
+use color_eyre::{Report, Result};
+use serde::Deserialize;
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::select;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, timeout};
+use tracing::{debug, error, trace, warn};
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+#[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
+pub enum ScriptInput {
+    String(String),
+    Struct(Script),
+}
+
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
+pub enum ScriptMode {
+    #[default]
+    Poll,
+    Watch,
+}
+
+/// How each line of script output is read.
+#[derive(Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
+pub enum OutputFormat {
+    /// Output is used as-is.
+    #[default]
+    Text,
+    /// Output is a JSON object,
+    /// in the same format as Waybar's custom module.
+    Json,
+}
+
+#[derive(Debug, Clone)]
+pub enum OutputStream {
+    Stdout(String),
+    Stderr(String),
+}
+
+impl From<&str> for ScriptMode {
+    fn from(str: &str) -> Self {
+        match str {
+            "poll" | "p" => Self::Poll,
+            "watch" | "w" => Self::Watch,
+            _ => {
+                warn!("Invalid script mode: '{str}', falling back to polling");
+                Self::Poll
+            }
+        }
+    }
+}
+
+impl Display for ScriptMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Poll => "poll",
+                Self::Watch => "watch",
+            }
+        )
+    }
+}
+
+impl ScriptMode {
+    fn try_parse(str: &str) -> Result<Self> {
+        match str {
+            "poll" | "p" => Ok(Self::Poll),
+            "watch" | "w" => Ok(Self::Watch),
+            _ => Err(Report::msg(format!("Invalid script mode: {str}"))),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "extras", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Script {
+    pub(crate) mode: ScriptMode,
+    pub cmd: String,
+    pub(crate) interval: u64,
+
+    /// Additional environment variables to set for the process.
+    pub(crate) env: BTreeMap<String, String>,
+
+    /// The working directory to run the process in.
+    /// Defaults to the working directory of Ironbar.
+    pub(crate) cwd: Option<PathBuf>,
+
+    /// The shell used to run `cmd`.
+    /// The command is passed to the shell with `-c`.
+    pub(crate) shell: String,
+
+    /// The program and its arguments to run directly,
+    /// without any shell interpretation.
+    /// When set, `cmd` and `shell` are ignored.
+    pub(crate) argv: Vec<String>,
+
+    /// The maximum number of milliseconds a single run may take
+    /// before the process is killed.
+    /// Has no effect on watch scripts.
+    pub(crate) timeout: Option<u64>,
+
+    /// How each line of output is read.
+    /// Only options which support structured output make use of this.
+    pub(crate) format: OutputFormat,
+
+    /// The maximum number of consecutive failures
+    /// before the script is no longer restarted.
+    /// By default, the script is always restarted.
+    ///
+    /// See [`Script::run_once`] for what counts as a failure.
+    pub(crate) max_restarts: Option<u32>,
+
+    /// The maximum number of milliseconds to wait before restarting a failing script.
+    /// The wait starts at `interval` and doubles after each consecutive failure,
+    /// up to this value.
+    pub(crate) max_backoff: u64,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self {
+            mode: ScriptMode::default(),
+            interval: 5000,
+            cmd: String::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+            shell: String::from("/bin/sh"),
+            argv: vec![],
+            timeout: None,
+            format: OutputFormat::default(),
+            max_restarts: None,
+            max_backoff: 60_000,
+        }
+    }
+}
+
+impl From<ScriptInput> for Script {
+    fn from(input: ScriptInput) -> Self {
+        match input {
+            ScriptInput::String(string) => Self::from(string.as_str()),
+            ScriptInput::Struct(script) => script,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ScriptInputToken {
+    Mode(ScriptMode),
+    Interval(u64),
+    Cmd(String),
+}
+
+#[derive(Debug, Copy, Clone)]
+enum CurrentToken {
+    Mode,
+    Interval,
+    Cmd,
+}
+
+impl From<&str> for Script {
+    fn from(str: &str) -> Self {
+        let mut script = Self::default();
+        let mut tokens = vec![];
+
+        let mut current_state = CurrentToken::Mode;
+
+        let mut chars = str.chars().collect::<Vec<_>>();
+        while !chars.is_empty() {
+            let char = chars[0];
+
+            let parse_res = match current_state {
+                CurrentToken::Mode => {
+                    current_state = CurrentToken::Interval;
+
+                    if matches!(char, 'p' | 'w') {
+                        let mode_str = chars.iter().take_while(|&c| c != &':').collect::<String>();
+                        let len = mode_str.len();
+
+                        let token = ScriptMode::try_parse(&mode_str).ok();
+                        token.map(|token| (ScriptInputToken::Mode(token), len))
+                    } else {
+                        None
+                    }
+                }
+                CurrentToken::Interval => {
+                    current_state = CurrentToken::Cmd;
+
+                    if char.is_ascii_digit() {
+                        let interval_str = chars
+                            .iter()
+                            .take_while(|c| c.is_ascii_digit())
+                            .collect::<String>();
+                        let len = interval_str.len();
+
+                        let token = interval_str.parse::<u64>().ok();
+                        token.map(|token| (ScriptInputToken::Interval(token), len))
+                    } else {
+                        None
+                    }
+                }
+                CurrentToken::Cmd => {
+                    let cmd_str = chars.iter().take_while(|_| true).collect::<String>();
+                    let len = cmd_str.len();
+                    Some((ScriptInputToken::Cmd(cmd_str), len))
+                }
+            };
+
+            if let Some((token, skip)) = parse_res {
+                tokens.push(token);
+                chars.drain(..min(skip + 1, chars.len())); // skip 1 extra for colon
+            }
+        }
+
+        for token in tokens {
+            match token {
+                ScriptInputToken::Mode(mode) => script.mode = mode,
+                ScriptInputToken::Interval(interval) => script.interval = interval,
+                ScriptInputToken::Cmd(cmd) => script.cmd = cmd,
+            }
+        }
+
+        script
+    }
+}
+
+impl Script {
+    pub fn new_polling(input: ScriptInput) -> Self {
+        let mut script = Self::from(input);
+        script.mode = ScriptMode::Poll;
+        script
+    }
+
+    /// Runs the script, passing `args` if provided.
+    /// Runs `f`, passing the output stream and whether the command returned 0.
+    ///
+    /// Scripts without `args` are shared through the [`ScriptRegistry`],
+    /// so that identical scripts used in several places only run once.
+    pub async fn run<F>(&self, args: Option<&[String]>, callback: F)
+    where
+        F: Fn(OutputStream, bool),
+    {
+        if args.is_some() {
+            return self.run_unshared(args, callback).await;
+        }
+
+        let subscription = Ironbar::script_registry().subscribe(self);
+
+        if let Some((output, success)) = subscription.last {
+            callback(output, success);
+        }
+
+        let mut rx = subscription.rx;
+        loop {
+            match rx.recv().await {
+                Ok((output, success)) => callback(output, success),
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    warn!("Script '{}' skipped {count} outputs", self.cmd);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    /// Runs the script in its own loop,
+    /// without sharing it with other subscribers.
+    async fn run_unshared<F>(&self, args: Option<&[String]>, callback: F)
+    where
+        F: Fn(OutputStream, bool),
+    {
+        let mut backoff = Backoff::default();
+
+        loop {
+            let success = self.run_once(args, &callback).await;
+
+            match backoff.next(self, success) {
+                Some(delay) => sleep(delay).await,
+                None => {
+                    error!("Script '{}' failed too many times, giving up", self.cmd);
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Runs the script once, until it exits,
+    /// passing each output to `callback`.
+    ///
+    /// Failures to run the script are passed to `callback` as `stderr`.
+    /// Returns whether the script ran without failing.
+    ///
+    /// A polling script which exits with a non-zero code has not failed,
+    /// as this is how conditions such as `show_if` return `false`.
+    async fn run_once<F>(&self, args: Option<&[String]>, callback: F) -> bool
+    where
+        F: Fn(OutputStream, bool),
+    {
+        let res = match self.mode {
+            ScriptMode::Poll => self.get_output(args).await.map(|(output, success)| {
+                callback(output, success);
+                true
+            }),
+            ScriptMode::Watch => match self.spawn() {
+                Ok((mut rx, handle)) => {
+                    while let Some(msg) = rx.recv().await {
+                        callback(msg, true);
+                    }
+
+                    match handle.await.ok().flatten() {
+                        Some(status) if !status.success() => {
+                            Err(Report::msg(format!("Process ended with {status}")))
+                        }
+                        _ => Ok(true),
+                    }
+                }
+                Err(err) => Err(err),
+            },
+        };
+
+        res.unwrap_or_else(|err| {
+            error!("{err:?}");
+            callback(OutputStream::Stderr(format!("{err:#}")), false);
+            false
+        })
+    }
+
+    /// Attempts to execute a given command,
+    /// waiting for it to finish.
+    /// If the command returns status 0,
+    /// the `stdout` is returned.
+    /// Otherwise, an `Err` variant
+    /// containing the `stderr` is returned.
+    pub async fn get_output(&self, args: Option<&[String]>) -> Result<(OutputStream, bool)> {
+        self.get_output_with_env(args, &[]).await
+    }
+
+    /// Like [`Script::get_output`],
+    /// but with additional environment variables set for the command.
+    pub async fn get_output_with_env(
+        &self,
+        args: Option<&[String]>,
+        env: &[(&str, String)],
+    ) -> Result<(OutputStream, bool)> {
+        let mut command = self.command(args);
+        command
+            .envs(env.iter().map(|(key, value)| (*key, value)))
+            .kill_on_drop(true);
+
+        // dropping the future on timeout kills the process
+        let output = match self.timeout {
+            Some(duration) => timeout(Duration::from_millis(duration), command.output())
+                .await
+                .map_err(|_| Report::msg(format!("Script timed out after {duration}ms")))?,
+            None => command.output().await,
+        }
+        .wrap_err("Failed to get script output")?;
+
+        trace!("Script output with args: {output:?}");
+
+        if output.status.success() {
+            let stdout = String::from_utf8(output.stdout)
+                .map(|output| output.trim().to_string())
+                .wrap_err("Script stdout not valid UTF-8")?;
+
+            debug!("sending stdout: '{stdout}'");
+
+            Ok((OutputStream::Stdout(stdout), true))
+        } else {
+            let stderr = String::from_utf8(output.stderr)
+                .map(|output| output.trim().to_string())
+                .wrap_err("Script stderr not valid UTF-8")?;
+
+            debug!("sending stderr: '{stderr}'");
+
+            Ok((OutputStream::Stderr(stderr), false))
+        }
+    }
+
+    /// Spawns a long-running process.
+    /// Returns a `mpsc::Receiver` that sends a message
+    /// every time a new line is written to `stdout` or `stderr`,
+    /// and a handle which resolves to the exit status once the process ends.
+    ///
+    /// The process is killed once the receiver is dropped.
+    pub fn spawn(&self) -> Result<(mpsc::Receiver<OutputStream>, JoinHandle<Option<ExitStatus>>)> {
+        let mut handle = self
+            .command(None)
+            .kill_on_drop(true)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .stdin(Stdio::null())
+            .spawn()?;
+
+        debug!("Spawned a long-running process for '{}'", self.cmd);
+        trace!("Handle: {:?}", handle);
+
+        let mut stdout_lines = BufReader::new(
+            handle
+                .stdout
+                .take()
+                .expect("Failed to take script handle stdout"),
+        )
+        .lines();
+
+        let mut stderr_lines = BufReader::new(
+            handle
+                .stderr
+                .take()
+                .expect("Failed to take script handle stderr"),
+        )
+        .lines();
+
+        let (tx, rx) = mpsc::channel(32);
+
+        let handle = spawn(async move {
+            // the process is killed when the handle is dropped
+            loop {
+                let sent = select! {
+                    status = handle.wait() => return status.ok(),
+                    () = tx.closed() => return None,
+                    Ok(Some(line)) = stdout_lines.next_line() => {
+                        debug!("sending stdout line: '{line}'");
+                        tx.send(OutputStream::Stdout(line)).await
+                    }
+                    Ok(Some(line)) = stderr_lines.next_line() => {
+                        debug!("sending stderr line: '{line}'");
+                        tx.send(OutputStream::Stderr(line)).await
+                    }
+                };
+
+                if sent.is_err() {
+                    return None;
+                }
+            }
+        });
+
+        Ok((rx, handle))
+    }
+
+    /// Builds the command to run,
+    /// either through the configured shell or directly from `argv`.
+    fn command(&self, args: Option<&[String]>) -> Command {
+        let mut command = match self.argv.split_first() {
+            Some((program, argv)) => {
+                let mut command = Command::new(program);
+                command.args(argv);
+                command
+            }
+            None => {
+                let mut command = Command::new(&self.shell);
+                command.args(["-c", &self.cmd]);
+                command
+            }
+        };
+
+        if let Some(args) = args {
+            command.args(args);
+        }
+
+        command.envs(&self.env);
+
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+
+        debug!("Running command: {command:?}");
+        command
+    }
+
+    /// Executes the script in oneshot mode,
+    /// meaning it is not awaited and output cannot be captured.
+    ///
+    /// If the script errors, this is logged.
+    ///
+    /// This has some overhead,
+    /// as the script has to be cloned to the thread.
+    ///
+    pub fn run_as_oneshot(&self, args: Option<&[String]>) {
+        self.run_as_oneshot_with_env(args, vec![]);
+    }
+
+    /// Like [`Script::run_as_oneshot`],
+    /// but with additional environment variables set for the command.
+    pub fn run_as_oneshot_with_env(
+        &self,
+        args: Option<&[String]>,
+        env: Vec<(&'static str, String)>,
+    ) {
+        let script = self.clone();
+        let args = args.map(<[String]>::to_vec);
+
+        spawn(async move {
+            match script.get_output_with_env(args.as_deref(), &env).await {
+                Ok((OutputStream::Stderr(out), _)) => error!("{out}"),
+                Err(err) => error!("{err:?}"),
+                _ => {}
+            }
+        });
+    }
+}
+
+/// Tracks consecutive failures of a script,
+/// to determine how long to wait before running it again.
+#[derive(Debug, Default)]
+struct Backoff {
+    failures: u32,
+}
+
+impl Backoff {
+    /// The shortest wait between failing runs,
+    /// to avoid spinning when the interval is very short.
+    const MIN_DELAY: u64 = 500;
+
+    /// Records the result of a run,
+    /// and gets the time to wait before the next one.
+    ///
+    /// Returns `None` if the script has failed too many times
+    /// and should not be restarted.
+    fn next(&mut self, script: &Script, success: bool) -> Option<Duration> {
+        if success {
+            self.failures = 0;
+            return Some(Duration::from_millis(script.interval));
+        }
+
+        self.failures += 1;
+
+        if script
+            .max_restarts
+            .is_some_and(|max_restarts| self.failures > max_restarts)
+        {
+            return None;
+        }
+
+        let base = script.interval.max(Self::MIN_DELAY);
+        let cap = script.max_backoff.max(base);
+        let delay = base.saturating_mul(1 << self.failures.min(16)).min(cap);
+
+        Some(Duration::from_millis(delay))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic() {
+        let default_script = Script::default();
+
+        let cmd = "echo 'hello'";
+        let script = Script::from(cmd);
+
+        assert_eq!(script.cmd, cmd);
+        assert_eq!(script.interval, default_script.interval);
+        assert_eq!(script.mode, ScriptMode::default());
+    }
+
+    #[test]
+    fn test_parse_full() {
+        let cmd = "echo 'hello'";
+        let mode = ScriptMode::Watch;
+        let interval = 300;
+
+        let full_cmd = format!("{mode}:{interval}:{cmd}");
+        let script = Script::from(full_cmd.as_str());
+
+        assert_eq!(script.cmd, cmd);
+        assert_eq!(script.mode, mode);
+        assert_eq!(script.interval, interval);
+    }
+
+    #[test]
+    fn test_parse_interval_and_cmd() {
+        let cmd = "echo 'hello'";
+        let interval = 300;
+
+        let full_cmd = format!("{interval}:{cmd}");
+        let script = Script::from(full_cmd.as_str());
+
+        assert_eq!(script.cmd, cmd);
+        assert_eq!(script.interval, interval);
+        assert_eq!(script.mode, ScriptMode::default());
+    }
+
+    #[test]
+    fn test_parse_mode_and_cmd() {
+        let default_script = Script::default();
+
+        let cmd = "echo 'hello'";
+        let mode = ScriptMode::Watch;
+
+        let full_cmd = format!("{mode}:{cmd}");
+        let script = Script::from(full_cmd.as_str());
+
+        assert_eq!(script.cmd, cmd);
+        assert_eq!(script.interval, default_script.interval);
+        assert_eq!(script.mode, mode);
+    }
+
+    #[test]
+    fn test_parse_cmd_with_colon() {
+        let default_script = Script::default();
+
+        let cmd = "uptime | awk '{print \"Uptime: \" $1}'";
+        let script = Script::from(cmd);
+
+        assert_eq!(script.cmd, cmd);
+        assert_eq!(script.interval, default_script.interval);
+        assert_eq!(script.mode, ScriptMode::default());
+    }
+
+    #[test]
+    fn test_no_cmd() {
+        let mode = ScriptMode::Watch;
+        let interval = 300;
+
+        let full_cmd = format!("{mode}:{interval}");
+        let script = Script::from(full_cmd.as_str());
+
+        assert_eq!(script.cmd, ""); // TODO: Probably better handle this case
+        assert_eq!(script.interval, interval);
+        assert_eq!(script.mode, mode);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let script = Script {
+            interval: 1000,
+            max_backoff: 5000,
+            max_restarts: Some(4),
+            ..Script::default()
+        };
+
+        let mut backoff = Backoff::default();
+        let mut next = |success| backoff.next(&script, success).map(|d| d.as_millis());
+
+        assert_eq!(next(false), Some(2000));
+        assert_eq!(next(false), Some(4000));
+        assert_eq!(next(false), Some(5000));
+        assert_eq!(next(true), Some(1000));
+        assert_eq!(next(false), Some(2000));
+        assert_eq!(next(false), Some(4000));
+        assert_eq!(next(false), Some(5000));
+        assert_eq!(next(false), Some(5000));
+        assert_eq!(next(false), None);
+    }
+
+    #[tokio::test]
+    async fn test_poll_exit_code_keeps_interval() {
+        let script = Script {
+            cmd: "exit 1".to_string(),
+            interval: 1000,
+            max_restarts: Some(0),
+            ..Script::default()
+        };
+
+        let mut backoff = Backoff::default();
+
+        for _ in 0..3 {
+            let ok = script.run_once(None, |_, success| assert!(!success)).await;
+            let delay = backoff.next(&script, ok).map(|d| d.as_millis());
+
+            assert_eq!(delay, Some(1000));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_env_and_cwd() {
+        let script = Script {
+            cmd: "echo \"$FOO $(pwd)\"".to_string(),
+            env: BTreeMap::from([("FOO".to_string(), "bar".to_string())]),
+            cwd: Some(PathBuf::from("/")),
+            ..Script::default()
+        };
+
+        let (output, success) = script.get_output(None).await.expect("failed to run script");
+        assert!(success);
+        assert!(matches!(output, OutputStream::Stdout(out) if out == "bar /"));
+    }
+
+    #[tokio::test]
+    async fn test_argv_no_shell() {
+        let script = Script {
+            argv: vec!["echo".to_string(), "$HOME".to_string()],
+            ..Script::default()
+        };
+
+        let (output, _) = script.get_output(None).await.expect("failed to run script");
+        assert!(matches!(output, OutputStream::Stdout(out) if out == "$HOME"));
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let script = Script {
+            cmd: "sleep 5".to_string(),
+            timeout: Some(50),
+            ..Script::default()
+        };
+
+        assert!(script.get_output(None).await.is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use super::{Backoff, OutputStream, Script, ScriptMode};
+use crate::{lock, spawn_shared};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::select;
+use tokio::sync::{Notify, broadcast};
+use tokio::time::sleep;
+use tracing::{debug, error};
+
+/// The output of a single script run,
+/// and whether the command returned 0.
+pub type ScriptEvent = (OutputStream, bool);
+
+/// A running script, shared between all of its subscribers.
+#[derive(Debug)]
+struct Entry {
+    tx: broadcast::Sender<ScriptEvent>,
+    last: Mutex<Option<ScriptEvent>>,
+    refresh: Notify,
+}
+
+impl Entry {
+    /// Sends an event to all subscribers,
+    /// storing it to be replayed to new subscribers.
+    fn send(&self, event: ScriptEvent) {
+        let mut last = lock!(self.last);
+
+        // an error here just means nobody is currently subscribed
+        let _ = self.tx.send(event.clone());
+        *last = Some(event);
+    }
+}
+
+/// A subscription to a shared script.
+#[derive(Debug)]
+pub struct Subscription {
+    /// The most recent output of the script, if it has run before.
+    pub last: Option<ScriptEvent>,
+    pub rx: broadcast::Receiver<ScriptEvent>,
+}
+
+/// Central registry of running scripts.
+///
+/// Each unique script is run only once,
+/// regardless of how many places it is used in,
+/// and its output is sent to every subscriber.
+/// Once the last subscriber is dropped, the script is stopped.
+#[derive(Debug, Default)]
+pub struct ScriptRegistry {
+    scripts: Mutex<HashMap<Script, Arc<Entry>>>,
+}
+
+impl ScriptRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to the output of `script`,
+    /// starting it if it is not already running.
+    pub fn subscribe(self: &Arc<Self>, script: &Script) -> Subscription {
+        let mut scripts = lock!(self.scripts);
+
+        if let Some(entry) = scripts.get(script) {
+            let last = lock!(entry.last);
+
+            return Subscription {
+                last: last.clone(),
+                rx: entry.tx.subscribe(),
+            };
+        }
+
+        let (tx, rx) = broadcast::channel(16);
+        let entry = Arc::new(Entry {
+            tx,
+            last: Mutex::new(None),
+            refresh: Notify::new(),
+        });
+
+        scripts.insert(script.clone(), entry.clone());
+
+        debug!("Starting shared script '{}'", script.cmd);
+        spawn_shared(self.clone().drive(script.clone(), entry));
+
+        Subscription { last: None, rx }
+    }
+
+    /// Runs `script` again immediately, if it is running.
+    /// Watch scripts are restarted.
+    ///
+    /// Returns whether the script was running.
+    pub fn refresh(&self, script: &Script) -> bool {
+        let scripts = lock!(self.scripts);
+
+        match scripts.get(script) {
+            Some(entry) => {
+                entry.refresh.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Runs `script` until it has no subscribers left,
+    /// or it has failed too many times.
+    async fn drive(self: Arc<Self>, script: Script, entry: Arc<Entry>) {
+        let mut backoff = Backoff::default();
+
+        loop {
+            // dropping the run early kills the process
+            let (success, refreshed) = select! {
+                success = script.run_once(None, |output, success| entry.send((output, success))) => (success, false),
+                () = entry.refresh.notified(), if script.mode == ScriptMode::Watch => (true, true),
+                () = entry.tx.closed() => (true, false),
+            };
+
+            let Some(delay) = backoff.next(&script, success) else {
+                error!("Script '{}' failed too many times, giving up", script.cmd);
+                lock!(self.scripts).remove(&script);
+                break;
+            };
+
+            if !refreshed {
+                select! {
+                    () = sleep(delay) => {},
+                    () = entry.refresh.notified() => {},
+                    () = entry.tx.closed() => {},
+                }
+            }
+
+            if self.remove_if_unused(&script) {
+                debug!("Stopped shared script '{}'", script.cmd);
+                break;
+            }
+        }
+    }
+
+    /// Removes `script` from the registry if it has no subscribers.
+    /// Returns whether it was removed.
+    ///
+    /// This is checked while the registry is locked,
+    /// so that a new subscriber cannot join a script that is stopping.
+    fn remove_if_unused(&self, script: &Script) -> bool {
+        let mut scripts = lock!(self.scripts);
+
+        let unused = scripts
+            .get(script)
+            .is_some_and(|entry| entry.tx.receiver_count() == 0);
+
+        if unused {
+            scripts.remove(script);
+        }
+
+        unused
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn script(cmd: &str) -> Script {
+        Script {
+            cmd: cmd.to_string(),
+            interval: 10,
+            ..Script::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deduplicates() {
+        let registry = Arc::new(ScriptRegistry::new());
+
+        let mut a = registry.subscribe(&script("echo a"));
+        let mut b = registry.subscribe(&script("echo a"));
+        let _c = registry.subscribe(&script("echo c"));
+
+        assert_eq!(lock!(registry.scripts).len(), 2);
+
+        for sub in [&mut a, &mut b] {
+            let (output, success) = sub.rx.recv().await.expect("failed to receive output");
+            assert!(success);
+            assert!(matches!(output, OutputStream::Stdout(out) if out == "a"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stops_without_subscribers() {
+        let registry = Arc::new(ScriptRegistry::new());
+
+        let mut sub = registry.subscribe(&script("echo a"));
+        sub.rx.recv().await.expect("failed to receive output");
+
+        let late = registry.subscribe(&script("echo a"));
+        assert!(late.last.is_some());
+
+        drop(sub);
+        drop(late);
+        sleep(Duration::from_millis(50)).await;
+
+        assert!(lock!(registry.scripts).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refresh() {
+        let registry = Arc::new(ScriptRegistry::new());
+        let script = Script {
+            cmd: "date +%s%N".to_string(),
+            interval: 60_000,
+            ..Script::default()
+        };
+
+        let mut sub = registry.subscribe(&script);
+        sub.rx.recv().await.expect("failed to receive output");
+
+        assert!(registry.refresh(&script));
+
+        let res = tokio::time::timeout(Duration::from_secs(1), sub.rx.recv()).await;
+        assert!(res.is_ok());
+    }
+}